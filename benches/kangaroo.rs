@@ -2,8 +2,8 @@
 
 extern crate test;
 
+use tari_tiny_keccak::{Hasher, KangarooTwelve};
 use test::Bencher;
-use tiny_keccak::{Hasher, KangarooTwelve};
 
 #[bench]
 fn bench_k12(b: &mut Bencher) {
@@ -2,8 +2,8 @@
 
 extern crate test;
 
+use tari_tiny_keccak::{keccak256_short, keccakf, Hasher, Keccak};
 use test::Bencher;
-use tiny_keccak::{keccakf, Hasher, Keccak};
 
 #[bench]
 fn bench_keccak_256_input_4096_bytes(b: &mut Bencher) {
@@ -18,8 +18,7 @@ fn bench_keccak_256_input_4096_bytes(b: &mut Bencher) {
     });
 }
 
-#[bench]
-fn keccakf_u64(b: &mut Bencher) {
+fn bench_keccakf(b: &mut Bencher) {
     const WORDS: usize = 25;
     b.bytes = (WORDS * 8) as u64;
 
@@ -29,6 +28,20 @@ fn keccakf_u64(b: &mut Bencher) {
     });
 }
 
+// `cargo bench --bench keccak` with and without the `bit_interleaving` feature compares the
+// bit-interleaved backend with the plain 64-bit lanes one.
+#[cfg(not(feature = "bit_interleaving"))]
+#[bench]
+fn keccakf_u64(b: &mut Bencher) {
+    bench_keccakf(b);
+}
+
+#[cfg(feature = "bit_interleaving")]
+#[bench]
+fn keccakf_interleaved(b: &mut Bencher) {
+    bench_keccakf(b);
+}
+
 #[bench]
 fn bench_keccak256(b: &mut Bencher) {
     let data = [0u8; 32];
@@ -41,3 +54,36 @@ fn bench_keccak256(b: &mut Bencher) {
         keccak.finalize(&mut res);
     });
 }
+
+#[bench]
+fn bench_keccak256_short(b: &mut Bencher) {
+    let data = [0u8; 32];
+    b.bytes = data.len() as u64;
+
+    b.iter(|| keccak256_short(test::black_box(&data)));
+}
+
+#[cfg(feature = "sha3")]
+#[bench]
+fn bench_sha3_256(b: &mut Bencher) {
+    use tari_tiny_keccak::Sha3;
+
+    let data = [0u8; 32];
+    b.bytes = data.len() as u64;
+
+    b.iter(|| {
+        let mut res: [u8; 32] = [0; 32];
+        let mut sha3 = Sha3::v256();
+        sha3.update(&data);
+        sha3.finalize(&mut res);
+    });
+}
+
+#[cfg(feature = "sha3")]
+#[bench]
+fn bench_sha3_256_short(b: &mut Bencher) {
+    let data = [0u8; 32];
+    b.bytes = data.len() as u64;
+
+    b.iter(|| tari_tiny_keccak::sha3_256_short(test::black_box(&data)));
+}
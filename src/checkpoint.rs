@@ -0,0 +1,73 @@
+//! Checkpoints of the `SP800-185` and `KangarooTwelve` hashers.
+//!
+//! The Borsh encoding of these hashers starts with a header made of the version of the encoding,
+//! a tag of the algorithm and its security level in bits. A checkpoint is only restored by the
+//! algorithm, and the security level, it was taken from.
+
+use crate::Error;
+use borsh::{io, BorshDeserialize, BorshSerialize};
+
+/// Version of the checkpoint encoding. Checkpoints of another version are rejected.
+const VERSION: u8 = 1;
+
+/// Tags of the algorithms in the header of a checkpoint.
+#[derive(Clone, Copy)]
+pub(crate) enum Algorithm {
+    #[cfg(feature = "kmac")]
+    Kmac = 1,
+    #[cfg(feature = "kmac")]
+    KmacXof = 2,
+    #[cfg(feature = "tuple_hash")]
+    TupleHash = 3,
+    #[cfg(feature = "tuple_hash")]
+    TupleHashXof = 4,
+    #[cfg(feature = "parallel_hash")]
+    ParallelHash = 5,
+    #[cfg(feature = "parallel_hash")]
+    ParallelHashXof = 6,
+    #[cfg(feature = "k12")]
+    KangarooTwelve = 7,
+    #[cfg(feature = "k12")]
+    KangarooTwelveXof = 8,
+    #[cfg(feature = "k12")]
+    KangarooTwelve256 = 9,
+    #[cfg(feature = "k12")]
+    KangarooTwelve256Xof = 10,
+    #[cfg(feature = "k12")]
+    MarsupilamiFourteen = 11,
+    #[cfg(feature = "k12")]
+    MarsupilamiFourteenXof = 12,
+}
+
+/// Writes the header of a checkpoint of `algorithm` with a security level of `bits` bits.
+pub(crate) fn write_header<W: io::Write>(
+    writer: &mut W,
+    algorithm: Algorithm,
+    bits: u16,
+) -> io::Result<()> {
+    VERSION.serialize(writer)?;
+    (algorithm as u8).serialize(writer)?;
+    bits.serialize(writer)
+}
+
+/// Reads the header of a checkpoint of `algorithm` and returns its security level in bits.
+pub(crate) fn read_header<R: io::Read>(reader: &mut R, algorithm: Algorithm) -> io::Result<u16> {
+    let version = u8::deserialize_reader(reader)?;
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version).into());
+    }
+    if u8::deserialize_reader(reader)? != algorithm as u8 {
+        return Err(Error::AlgorithmMismatch.into());
+    }
+    u16::deserialize_reader(reader)
+}
+
+/// Reads a counter, that is encoded as a `u64`.
+#[cfg(any(feature = "k12", feature = "parallel_hash"))]
+pub(crate) fn read_len<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let len = u64::deserialize_reader(reader)?;
+    if len > usize::MAX as u64 {
+        return Err(Error::InvalidState.into());
+    }
+    Ok(len as usize)
+}
@@ -2,7 +2,13 @@
 //!
 //! [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 
-use crate::{bits_to_rate, keccakf::KeccakF, left_encode, Hasher, KeccakState, Xof};
+#[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+use crate::bits_to_rate;
+use crate::{
+    keccakf::KeccakF, left_encode, or_panic, security_level_to_rate, Error, Hasher, KeccakState,
+    Permutation, Xof, STATE_BYTES,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 /// The `cSHAKE` extendable-output functions defined in [`SP800-185`].
 ///
@@ -15,13 +21,26 @@ use crate::{bits_to_rate, keccakf::KeccakF, left_encode, Hasher, KeccakState, Xo
 ///
 /// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 #[derive(Clone)]
-pub struct CShake {
-    state: KeccakState<KeccakF>,
+pub struct CShake<P = KeccakF> {
+    state: KeccakState<P>,
 }
 
-impl CShake {
-    const DELIM: u8 = 0x04;
+// Default permutation only, like `Keccak`.
+impl BorshSerialize for CShake {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for CShake {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(CShake::from_state(KeccakState::deserialize_with_delim(
+            reader,
+        )?)?)
+    }
+}
 
+impl CShake {
     /// Creates  new [`CShake`] hasher with a security level of 128 bits.
     ///
     /// [`CShake`]: struct.CShake.html
@@ -35,14 +54,34 @@ impl CShake {
     pub fn v256(name: &[u8], custom_string: &[u8]) -> CShake {
         CShake::new(name, custom_string, 256)
     }
+}
 
-    pub(crate) fn new(name: &[u8], custom_string: &[u8], bits: u16) -> CShake {
-        let rate = bits_to_rate(bits);
+impl<P: Permutation> CShake<P> {
+    const DELIM: u8 = 0x04;
+
+    /// Creates new [`CShake`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 128 or 256.
+    ///
+    /// [`CShake`]: struct.CShake.html
+    pub fn new(name: &[u8], custom_string: &[u8], bits: u16) -> CShake<P> {
+        or_panic(Self::try_new(name, custom_string, bits))
+    }
+
+    /// Creates new [`CShake`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 128 or 256.
+    ///
+    /// [`CShake`]: struct.CShake.html
+    pub fn try_new(name: &[u8], custom_string: &[u8], bits: u16) -> Result<CShake<P>, Error> {
+        let rate = security_level_to_rate(bits, &[128, 256])?;
         // if there is no name and no customization string
         // cSHAKE is SHAKE
         if name.is_empty() && custom_string.is_empty() {
             let state = KeccakState::new(rate, 0x1f);
-            return CShake { state };
+            return Ok(CShake { state });
         }
 
         let mut state = KeccakState::new(rate, Self::DELIM);
@@ -51,16 +90,90 @@ impl CShake {
         state.update(name);
         state.update(left_encode(custom_string.len() * 8).value());
         state.update(custom_string);
-        state.fill_block();
-        CShake { state }
+        state.bytepad();
+        Ok(CShake { state })
+    }
+
+    #[cfg(feature = "kmac")]
+    pub(crate) fn bytepad(&mut self) {
+        self.state.bytepad();
+    }
+
+    /// The security level of the hasher in bits.
+    #[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+    pub(crate) fn bits(&self) -> u16 {
+        if self.state.rate == bits_to_rate(128) {
+            128
+        } else {
+            256
+        }
+    }
+
+    /// Checks that a decoded state has a security level of `bits` bits and the suffix `delim`,
+    /// `0x04` for cSHAKE or `0x1f` when it is SHAKE.
+    #[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+    pub(crate) fn check(&self, bits: u16, delim: u8) -> Result<(), Error> {
+        if self.state.rate != bits_to_rate(bits) || self.state.delim != delim {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// Checks that a restored state has not started squeezing, see
+    /// `KeccakState::check_absorbing`.
+    #[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+    pub(crate) fn check_absorbing(&self) -> Result<(), Error> {
+        self.state.check_absorbing()
     }
 
-    pub(crate) fn fill_block(&mut self) {
-        self.state.fill_block();
+    /// Encodes the state of the hasher in the layout described in [`STATE_BYTES`].
+    ///
+    /// [`STATE_BYTES`]: constant.STATE_BYTES.html
+    pub fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        self.state.to_bytes()
+    }
+
+    /// Decodes a state encoded by [`to_bytes`]. Fails with [`Error::InvalidState`] if `bytes`
+    /// is not the state of a [`CShake`] hasher.
+    ///
+    /// [`to_bytes`]: struct.CShake.html#method.to_bytes
+    /// [`CShake`]: struct.CShake.html
+    /// [`Error::InvalidState`]: enum.Error.html#variant.InvalidState
+    pub fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<CShake<P>, Error> {
+        Self::from_state(KeccakState::from_bytes(bytes)?)
+    }
+
+    /// Wraps a decoded state, see `KeccakState::validate`.
+    fn from_state(state: KeccakState<P>) -> Result<CShake<P>, Error> {
+        state.validate(&[128, 256], |delim| delim == Self::DELIM || delim == 0x1f)?;
+        Ok(CShake { state })
+    }
+
+    /// Absorbs the first `bit_len` bits of `input`, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is shorter than `bit_len` bits, or if trailing bits that don't fill a
+    /// byte have already been absorbed.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        self.state.update_bits(input, bit_len);
+    }
+
+    /// Fills `output` with the next `bit_len` bits of the output, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is shorter than `bit_len` bits.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn squeeze_bits(&mut self, output: &mut [u8], bit_len: usize) {
+        self.state.squeeze_bits(output, bit_len);
     }
 }
 
-impl Hasher for CShake {
+impl<P: Permutation> Hasher for CShake<P> {
     fn update(&mut self, input: &[u8]) {
         self.state.update(input);
     }
@@ -70,7 +183,7 @@ impl Hasher for CShake {
     }
 }
 
-impl Xof for CShake {
+impl<P: Permutation> Xof for CShake<P> {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
@@ -0,0 +1,526 @@
+//! `digest` support for the hashers.
+//!
+//! The hashers of the crate implement `Update`, and the extendable-output functions
+//! `ExtendableOutput`, with their `Xof` types as readers. The `digest` traits that need the output
+//! size, the security level or the key size in the type, like `FixedOutput`, `Reset` or
+//! `KeyInit`, are implemented by the types of this module, that wrap a hasher with fixed
+//! parameters. The SHA-3, SHAKE and KMAC types also carry their object identifier.
+//!
+//! The core traits of `digest`, like `UpdateCore` and `FixedOutputCore`, are not implemented, so
+//! HMAC is only available through `hmac::SimpleHmac`.
+
+#[cfg(any(
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "k12"
+))]
+use crate::IntoXof;
+#[cfg(any(
+    feature = "keccak",
+    feature = "sha3",
+    feature = "shake",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash"
+))]
+use crate::Permutation;
+#[cfg(any(
+    feature = "shake",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "k12",
+    feature = "turbo_shake"
+))]
+use crate::Xof;
+#[cfg(any(feature = "sha3", feature = "shake", feature = "kmac"))]
+use digest::const_oid::{AssociatedOid, ObjectIdentifier};
+#[cfg(any(
+    feature = "keccak",
+    feature = "sha3",
+    feature = "shake",
+    feature = "kmac"
+))]
+use digest::Reset;
+#[cfg(any(
+    feature = "shake",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "k12",
+    feature = "turbo_shake"
+))]
+use digest::{ExtendableOutput, XofReader};
+#[cfg(any(feature = "keccak", feature = "sha3", feature = "kmac"))]
+use digest::{FixedOutput, FixedOutputReset, Output, OutputSizeUser};
+#[cfg(any(
+    feature = "keccak",
+    feature = "sha3",
+    feature = "shake",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "k12",
+    feature = "turbo_shake"
+))]
+use {crate::Hasher, digest::Update};
+
+#[cfg(feature = "cshake")]
+use crate::CShake;
+#[cfg(feature = "keccak")]
+use crate::Keccak;
+#[cfg(feature = "sha3")]
+use crate::Sha3;
+#[cfg(feature = "shake")]
+use crate::Shake;
+#[cfg(feature = "turbo_shake")]
+use crate::TurboShake;
+#[cfg(feature = "k12")]
+use crate::{
+    KangarooTwelve, KangarooTwelve256, KangarooTwelve256Xof, KangarooTwelveXof,
+    MarsupilamiFourteen, MarsupilamiFourteenXof,
+};
+#[cfg(feature = "kmac")]
+use crate::{Kmac, KmacXof};
+#[cfg(feature = "parallel_hash")]
+use crate::{ParallelHash, ParallelHashXof};
+#[cfg(feature = "tuple_hash")]
+use crate::{TupleHash, TupleHashXof};
+#[cfg(feature = "shake")]
+use digest::ExtendableOutputReset;
+#[cfg(any(feature = "keccak", feature = "sha3"))]
+use digest::{crypto_common::BlockSizeUser, HashMarker};
+#[cfg(feature = "kmac")]
+use digest::{
+    crypto_common::{InvalidLength, Key, KeyInit, KeySizeUser},
+    MacMarker,
+};
+
+/// Implements `Update` for hashers generic over their permutation.
+#[cfg(any(
+    feature = "keccak",
+    feature = "sha3",
+    feature = "shake",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash"
+))]
+macro_rules! update {
+    ($($ty:ident),*) => {
+        $(
+            impl<P: Permutation> Update for $ty<P> {
+                fn update(&mut self, data: &[u8]) {
+                    Hasher::update(self, data);
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `ExtendableOutput` for extendable-output functions generic over their
+/// permutation, read with the `Xof` type of `IntoXof`, and `XofReader` for that type.
+#[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+macro_rules! extendable_output {
+    ($($ty:ident => $xof:ident),*) => {
+        $(
+            impl<P: Permutation> ExtendableOutput for $ty<P> {
+                type Reader = $xof<P>;
+
+                fn finalize_xof(self) -> $xof<P> {
+                    self.into_xof()
+                }
+            }
+
+            impl<P: Permutation> XofReader for $xof<P> {
+                fn read(&mut self, buffer: &mut [u8]) {
+                    self.squeeze(buffer);
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "keccak")]
+update!(Keccak);
+
+#[cfg(feature = "sha3")]
+update!(Sha3);
+
+#[cfg(feature = "shake")]
+update!(Shake);
+
+#[cfg(feature = "cshake")]
+update!(CShake);
+
+#[cfg(feature = "kmac")]
+update!(Kmac);
+
+#[cfg(feature = "tuple_hash")]
+update!(TupleHash);
+
+#[cfg(feature = "parallel_hash")]
+update!(ParallelHash);
+
+#[cfg(feature = "kmac")]
+extendable_output!(Kmac => KmacXof);
+
+#[cfg(feature = "tuple_hash")]
+extendable_output!(TupleHash => TupleHashXof);
+
+#[cfg(feature = "parallel_hash")]
+extendable_output!(ParallelHash => ParallelHashXof);
+
+// `Shake`, `CShake` and `TurboShake` are squeezed directly, so they are their own reader.
+#[cfg(feature = "shake")]
+impl<P: Permutation> ExtendableOutput for Shake<P> {
+    type Reader = Shake<P>;
+
+    fn finalize_xof(self) -> Shake<P> {
+        self
+    }
+}
+
+#[cfg(feature = "shake")]
+impl<P: Permutation> XofReader for Shake<P> {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.squeeze(buffer);
+    }
+}
+
+#[cfg(feature = "cshake")]
+impl<P: Permutation> ExtendableOutput for CShake<P> {
+    type Reader = CShake<P>;
+
+    fn finalize_xof(self) -> CShake<P> {
+        self
+    }
+}
+
+#[cfg(feature = "cshake")]
+impl<P: Permutation> XofReader for CShake<P> {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.squeeze(buffer);
+    }
+}
+
+#[cfg(feature = "turbo_shake")]
+impl Update for TurboShake {
+    fn update(&mut self, data: &[u8]) {
+        Hasher::update(self, data);
+    }
+}
+
+#[cfg(feature = "turbo_shake")]
+impl ExtendableOutput for TurboShake {
+    type Reader = TurboShake;
+
+    fn finalize_xof(self) -> TurboShake {
+        self
+    }
+}
+
+#[cfg(feature = "turbo_shake")]
+impl XofReader for TurboShake {
+    fn read(&mut self, buffer: &mut [u8]) {
+        self.squeeze(buffer);
+    }
+}
+
+#[cfg(feature = "k12")]
+macro_rules! kangaroo {
+    ($($ty:ident => $xof:ident),*) => {
+        $(
+            impl<T: AsRef<[u8]>> Update for $ty<T> {
+                fn update(&mut self, data: &[u8]) {
+                    Hasher::update(self, data);
+                }
+            }
+
+            impl<T: AsRef<[u8]>> ExtendableOutput for $ty<T> {
+                type Reader = $xof;
+
+                fn finalize_xof(self) -> $xof {
+                    self.into_xof()
+                }
+            }
+
+            impl XofReader for $xof {
+                fn read(&mut self, buffer: &mut [u8]) {
+                    self.squeeze(buffer);
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "k12")]
+kangaroo!(
+    KangarooTwelve => KangarooTwelveXof,
+    KangarooTwelve256 => KangarooTwelve256Xof,
+    MarsupilamiFourteen => MarsupilamiFourteenXof
+);
+
+/// Defines a hash function with a fixed output, wrapping a hasher with a security level of
+/// `bits` bits, with its output size, its block size (the rate) and an optional object
+/// identifier.
+#[cfg(any(feature = "keccak", feature = "sha3"))]
+macro_rules! fixed_output {
+    ($(#[$doc:meta])* $name:ident($inner:ident, $bits:expr, $output:ty, $block:ty $(, $oid:expr)?)) => {
+        $(#[$doc])*
+        ///
+        /// HMAC is computed with `hmac::SimpleHmac`. `hmac::Hmac` needs the block-level core
+        /// traits of `digest`, which are not implemented.
+        #[derive(Clone)]
+        pub struct $name($inner);
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name(<$inner>::new($bits))
+            }
+        }
+
+        impl HashMarker for $name {}
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $output;
+        }
+
+        impl BlockSizeUser for $name {
+            type BlockSize = $block;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                Hasher::update(&mut self.0, data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(self, out: &mut Output<$name>) {
+                Hasher::finalize(self.0, out);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = $name::default();
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<$name>) {
+                core::mem::take(self).finalize_into(out);
+            }
+        }
+
+        $(
+            impl AssociatedOid for $name {
+                const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($oid);
+            }
+        )?
+    };
+}
+
+#[cfg(feature = "sha3")]
+fixed_output!(
+    /// `SHA3-224`, with the `digest` traits.
+    Sha3_224(Sha3, 224, digest::consts::U28, digest::consts::U144, "2.16.840.1.101.3.4.2.7")
+);
+
+#[cfg(feature = "sha3")]
+fixed_output!(
+    /// `SHA3-256`, with the `digest` traits.
+    Sha3_256(Sha3, 256, digest::consts::U32, digest::consts::U136, "2.16.840.1.101.3.4.2.8")
+);
+
+#[cfg(feature = "sha3")]
+fixed_output!(
+    /// `SHA3-384`, with the `digest` traits.
+    Sha3_384(Sha3, 384, digest::consts::U48, digest::consts::U104, "2.16.840.1.101.3.4.2.9")
+);
+
+#[cfg(feature = "sha3")]
+fixed_output!(
+    /// `SHA3-512`, with the `digest` traits.
+    Sha3_512(Sha3, 512, digest::consts::U64, digest::consts::U72, "2.16.840.1.101.3.4.2.10")
+);
+
+#[cfg(feature = "keccak")]
+fixed_output!(
+    /// `Keccak-224`, with the `digest` traits. It has no object identifier.
+    Keccak224(Keccak, 224, digest::consts::U28, digest::consts::U144)
+);
+
+#[cfg(feature = "keccak")]
+fixed_output!(
+    /// `Keccak-256`, with the `digest` traits. It has no object identifier.
+    Keccak256(Keccak, 256, digest::consts::U32, digest::consts::U136)
+);
+
+#[cfg(feature = "keccak")]
+fixed_output!(
+    /// `Keccak-384`, with the `digest` traits. It has no object identifier.
+    Keccak384(Keccak, 384, digest::consts::U48, digest::consts::U104)
+);
+
+#[cfg(feature = "keccak")]
+fixed_output!(
+    /// `Keccak-512`, with the `digest` traits. It has no object identifier.
+    Keccak512(Keccak, 512, digest::consts::U64, digest::consts::U72)
+);
+
+/// Defines an extendable-output function wrapping a `Shake` with a security level of `bits`
+/// bits, with its object identifier.
+#[cfg(feature = "shake")]
+macro_rules! shake {
+    ($(#[$doc:meta])* $name:ident($bits:expr, $oid:expr)) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(Shake);
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name(Shake::new($bits))
+            }
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                Hasher::update(&mut self.0, data);
+            }
+        }
+
+        impl ExtendableOutput for $name {
+            type Reader = Shake;
+
+            fn finalize_xof(self) -> Shake {
+                self.0
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = $name::default();
+            }
+        }
+
+        impl ExtendableOutputReset for $name {
+            fn finalize_xof_reset(&mut self) -> Shake {
+                core::mem::take(self).finalize_xof()
+            }
+        }
+
+        impl AssociatedOid for $name {
+            const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($oid);
+        }
+    };
+}
+
+#[cfg(feature = "shake")]
+shake!(
+    /// `SHAKE128`, with the `digest` traits.
+    Shake128(128, "2.16.840.1.101.3.4.2.11")
+);
+
+#[cfg(feature = "shake")]
+shake!(
+    /// `SHAKE256`, with the `digest` traits.
+    Shake256(256, "2.16.840.1.101.3.4.2.12")
+);
+
+/// Defines a `KMAC` with an empty customization string and a fixed output, with the `Mac`
+/// traits. Its object identifier is the one of [`RFC 8702`], whose output lengths it uses.
+///
+/// [`RFC 8702`]: https://www.rfc-editor.org/rfc/rfc8702.html
+#[cfg(feature = "kmac")]
+macro_rules! kmac {
+    ($(#[$doc:meta])* $name:ident($bits:expr, $key:ty, $output:ty, $oid:expr)) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            state: Kmac,
+            /// The keyed state, restored by `reset`.
+            initial: Kmac,
+        }
+
+        impl MacMarker for $name {}
+
+        impl KeySizeUser for $name {
+            type KeySize = $key;
+        }
+
+        impl KeyInit for $name {
+            fn new(key: &Key<$name>) -> $name {
+                $name::new_with_key(key)
+            }
+
+            /// Creates the MAC with a key of any length, as allowed by `SP800-185`.
+            fn new_from_slice(key: &[u8]) -> Result<$name, InvalidLength> {
+                Ok($name::new_with_key(key))
+            }
+        }
+
+        impl $name {
+            fn new_with_key(key: &[u8]) -> $name {
+                let state = Kmac::new(key, b"", $bits);
+                $name {
+                    initial: state.clone(),
+                    state,
+                }
+            }
+        }
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $output;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                Hasher::update(&mut self.state, data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(self, out: &mut Output<$name>) {
+                Hasher::finalize(self.state, out);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                self.state = self.initial.clone();
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<$name>) {
+                let state = core::mem::replace(&mut self.state, self.initial.clone());
+                Hasher::finalize(state, out);
+            }
+        }
+
+        impl AssociatedOid for $name {
+            const OID: ObjectIdentifier = ObjectIdentifier::new_unwrap($oid);
+        }
+    };
+}
+
+#[cfg(feature = "kmac")]
+kmac!(
+    /// `KMAC128` with a 256-bit output, with the `digest` traits. `KeyInit::new_from_slice`
+    /// takes a key of any length.
+    Kmac128(128, digest::consts::U16, digest::consts::U32, "2.16.840.1.101.3.4.2.19")
+);
+
+#[cfg(feature = "kmac")]
+kmac!(
+    /// `KMAC256` with a 512-bit output, with the `digest` traits. `KeyInit::new_from_slice`
+    /// takes a key of any length.
+    Kmac256(256, digest::consts::U32, digest::consts::U64, "2.16.840.1.101.3.4.2.20")
+);
@@ -0,0 +1,155 @@
+//! The duplex construction of [`Duplexing the sponge`].
+//!
+//! [`Duplexing the sponge`]: https://keccak.team/files/SpongeDuplex.pdf
+
+use crate::{capacity_to_rate, keccakf::KeccakF, or_panic, Error, KeccakState, Permutation, WORDS};
+use borsh::{io, BorshDeserialize, BorshSerialize};
+
+/// The `Keccak` duplex object defined in [`Duplexing the sponge`].
+///
+/// Every call to [`duplexing`] absorbs an input shorter than the rate, padded with `pad10*1`,
+/// permutes the state once and returns up to a rate of output. The output of a call depends on
+/// all the inputs given so far, like the output of a sponge fed with the padded inputs.
+///
+/// A duplex object with a capacity of `c` bits has the same generic security as a sponge with
+/// the same capacity: `c/2` bits, as long as the number of calls stays far below `2^(c/2)`.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["duplex"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::Duplex;
+/// let mut duplex = Duplex::v128();
+/// let mut tag = [0u8; 16];
+/// duplex.duplexing(b"key", &mut []);
+/// duplex.duplexing(b"message", &mut tag);
+/// ```
+///
+/// [`Duplexing the sponge`]: https://keccak.team/files/SpongeDuplex.pdf
+/// [`duplexing`]: struct.Duplex.html#method.duplexing
+#[derive(Clone)]
+pub struct Duplex<P = KeccakF> {
+    state: KeccakState<P>,
+}
+
+// Implemented for the default permutation only, like `Keccak`, whose encoding also leaves the
+// suffix implicit.
+impl BorshSerialize for Duplex {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Duplex {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let state = KeccakState::deserialize_reader(reader)?;
+        // every call absorbs and permutes a whole block, so nothing is left half absorbed
+        state.check_absorbing()?;
+        if state.offset != 0 {
+            return Err(Error::InvalidState.into());
+        }
+        Ok(Duplex { state })
+    }
+}
+
+impl Duplex {
+    /// Creates new [`Duplex`] object with a capacity of 256 bits and a security level of 128
+    /// bits.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn v128() -> Duplex {
+        Duplex::new(256)
+    }
+
+    /// Creates new [`Duplex`] object with a capacity of 512 bits and a security level of 256
+    /// bits.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn v256() -> Duplex {
+        Duplex::new(512)
+    }
+}
+
+impl<P: Permutation> Duplex<P> {
+    const DELIM: u8 = 0x01;
+
+    /// Creates new [`Duplex`] object with a capacity of `capacity` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is not a whole number of bytes, of at least 2 bytes and smaller than the
+    /// width of the permutation.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn new(capacity: usize) -> Duplex<P> {
+        or_panic(Self::try_new(capacity))
+    }
+
+    /// Creates new [`Duplex`] object with a capacity of `capacity` bits, that uses the
+    /// permutation `P`. Fails if the rate is not a whole number of bytes, of at least 2 bytes and
+    /// smaller than the width of the permutation.
+    ///
+    /// [`Duplex`]: struct.Duplex.html
+    pub fn try_new(capacity: usize) -> Result<Duplex<P>, Error> {
+        Ok(Duplex {
+            state: KeccakState::new(capacity_to_rate(capacity, P::WIDTH)?, Self::DELIM),
+        })
+    }
+
+    /// The rate of the duplex object in bytes.
+    pub fn rate(&self) -> usize {
+        self.state.rate as usize
+    }
+
+    /// Absorbs `input`, permutes the state and fills `output` with the outer part of the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is not shorter than the rate or if `output` is longer than the rate.
+    pub fn duplexing(&mut self, input: &[u8], output: &mut [u8]) {
+        let rate = self.check(input, output);
+        self.state.buffer.xorin(input, 0, input.len());
+        self.state.buffer.pad(input.len(), Self::DELIM, rate);
+        self.state.keccak();
+        self.state.setout(output, 0, output.len());
+    }
+
+    /// Like [`duplexing`], but the padded `input` overwrites the outer part of the state instead
+    /// of being added to it. This is the overwrite mode of the duplex construction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is not shorter than the rate or if `output` is longer than the rate.
+    ///
+    /// [`duplexing`]: struct.Duplex.html#method.duplexing
+    pub fn duplexing_overwrite(&mut self, input: &[u8], output: &mut [u8]) {
+        let rate = self.check(input, output);
+        // adding the outer part of the state to the padded input overwrites it
+        let mut block = [0u8; WORDS * 8];
+        self.state.setout(&mut block, 0, rate);
+        for (byte, input) in block.iter_mut().zip(input) {
+            *byte ^= *input;
+        }
+        self.state.buffer.xorin(&block, 0, rate);
+        self.state.buffer.pad(input.len(), Self::DELIM, rate);
+        self.state.keccak();
+        self.state.setout(output, 0, output.len());
+    }
+
+    fn check(&self, input: &[u8], output: &[u8]) -> usize {
+        let rate = self.rate();
+        assert!(input.len() < rate, "input must be shorter than the rate");
+        assert!(
+            output.len() <= rate,
+            "output cannot be longer than the rate"
+        );
+        rate
+    }
+}
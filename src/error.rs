@@ -0,0 +1,82 @@
+//! The error type of the fallible constructors and state decoders.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::ToString;
+use borsh::io;
+use core::fmt;
+
+/// An error returned by the `try_*` constructors and by the state decoders.
+///
+/// The panicking constructors panic with the message of the error they would return.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The security level, in bits, is not one of those of the function.
+    InvalidSecurityLevel(u16),
+    /// The capacity gives a rate that is not a whole number of bytes.
+    FractionalRate,
+    /// The rate is smaller than 2 bytes, or not smaller than the width of the permutation.
+    RateOutOfRange,
+    /// The domain separation suffix is longer than 6 bits.
+    SuffixTooLong,
+    /// The domain separation suffix has bits set above its length, given in bits.
+    SuffixOverflow(u8),
+    /// The domain separation byte of `TurboSHAKE` is not in range `0x01..=0x7F`.
+    InvalidDomainByte(u8),
+    /// The block size of `ParallelHash` is zero.
+    ZeroBlockSize,
+    /// A decoded state is not the state of the hasher it is decoded into.
+    InvalidState,
+    /// A checkpoint has a version of the encoding that is not supported.
+    UnsupportedVersion(u8),
+    /// A checkpoint was taken from another algorithm, or from another security level.
+    AlgorithmMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidSecurityLevel(bits) => {
+                write!(f, "security level of {} bits is not supported", bits)
+            }
+            Error::FractionalRate => f.write_str("rate must be a whole number of bytes"),
+            Error::RateOutOfRange => f.write_str(
+                "rate must be at least 2 bytes and smaller than the width of the permutation",
+            ),
+            Error::SuffixTooLong => f.write_str("suffix cannot be longer than 6 bits"),
+            Error::SuffixOverflow(len) => write!(f, "suffix must fit in {} bits", len),
+            Error::InvalidDomainByte(byte) => write!(
+                f,
+                "domain separation byte {:#04x} is not in range 0x01..=0x7F",
+                byte
+            ),
+            Error::ZeroBlockSize => f.write_str("block size cannot be 0"),
+            Error::InvalidState => f.write_str("invalid sponge state"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {}", version)
+            }
+            Error::AlgorithmMismatch => {
+                f.write_str("checkpoint of another algorithm or security level")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Keeps the error as the source of the `io::Error`, to be recovered with `get_ref` and
+/// `downcast_ref`.
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+    }
+}
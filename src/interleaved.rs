@@ -0,0 +1,140 @@
+//! Bit-interleaved representation of `keccak-f[1600]` lanes.
+//!
+//! Every 64-bit lane is split into two 32-bit words, one holding the even and one holding the odd
+//! bits of the lane. A 64-bit rotation then becomes two 32-bit rotations, which is much cheaper on
+//! targets without native 64-bit registers.
+
+use crate::{keccakf, WORDS};
+use core::ops::{BitAnd, BitXor, BitXorAssign, Not};
+
+#[derive(Clone, Copy, Default, Debug, PartialEq)]
+pub(crate) struct Interleaved {
+    even: u32,
+    odd: u32,
+}
+
+/// Round constants of `keccak-f[1600]`, interleaved once at compile time so that iota doesn't
+/// convert them every round.
+pub(crate) const RC: [Interleaved; 24] = {
+    let mut rc = [Interleaved { even: 0, odd: 0 }; 24];
+    let mut i = 0;
+    while i < rc.len() {
+        rc[i] = Interleaved::from_lane(keccakf::RC[i]);
+        i += 1;
+    }
+    rc
+};
+
+/// Gathers the even bits of `x` in the lower half of the result.
+#[inline(always)]
+const fn compact(x: u64) -> u32 {
+    let mut x = x & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x >> 16)) & 0x0000_0000_ffff_ffff;
+    x as u32
+}
+
+/// Spreads the bits of `x` over the even bits of the result.
+#[inline(always)]
+fn spread(x: u32) -> u64 {
+    let mut x = x as u64;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    x = (x | (x << 1)) & 0x5555_5555_5555_5555;
+    x
+}
+
+impl Interleaved {
+    #[inline(always)]
+    pub(crate) const fn from_lane(lane: u64) -> Interleaved {
+        Interleaved {
+            even: compact(lane),
+            odd: compact(lane >> 1),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn to_lane(self) -> u64 {
+        spread(self.even) | (spread(self.odd) << 1)
+    }
+
+    #[inline(always)]
+    pub(crate) fn load(a: &[u64; WORDS]) -> [Interleaved; WORDS] {
+        let mut lanes = [Interleaved::default(); WORDS];
+        for (lane, word) in lanes.iter_mut().zip(a.iter()) {
+            *lane = Interleaved::from_lane(*word);
+        }
+        lanes
+    }
+
+    #[inline(always)]
+    pub(crate) fn store(lanes: &[Interleaved; WORDS], a: &mut [u64; WORDS]) {
+        for (word, lane) in a.iter_mut().zip(lanes.iter()) {
+            *word = lane.to_lane();
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn rotate_left(self, n: u32) -> Interleaved {
+        let half = n / 2;
+        if n & 1 == 0 {
+            Interleaved {
+                even: self.even.rotate_left(half),
+                odd: self.odd.rotate_left(half),
+            }
+        } else {
+            Interleaved {
+                even: self.odd.rotate_left(half + 1),
+                odd: self.even.rotate_left(half),
+            }
+        }
+    }
+}
+
+impl BitXor for Interleaved {
+    type Output = Interleaved;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: Interleaved) -> Interleaved {
+        Interleaved {
+            even: self.even ^ rhs.even,
+            odd: self.odd ^ rhs.odd,
+        }
+    }
+}
+
+impl BitXorAssign for Interleaved {
+    #[inline(always)]
+    fn bitxor_assign(&mut self, rhs: Interleaved) {
+        *self = *self ^ rhs;
+    }
+}
+
+impl BitAnd for Interleaved {
+    type Output = Interleaved;
+
+    #[inline(always)]
+    fn bitand(self, rhs: Interleaved) -> Interleaved {
+        Interleaved {
+            even: self.even & rhs.even,
+            odd: self.odd & rhs.odd,
+        }
+    }
+}
+
+impl Not for Interleaved {
+    type Output = Interleaved;
+
+    #[inline(always)]
+    fn not(self) -> Interleaved {
+        Interleaved {
+            even: !self.even,
+            odd: !self.odd,
+        }
+    }
+}
@@ -1,8 +1,18 @@
-//! The `KangarooTwelve` hash function defined [`here`].
+//! The `KangarooTwelve` and `MarsupilamiFourteen` hash functions defined [`here`] and in
+//! [`RFC 9861`].
 //!
 //! [`here`]: https://eprint.iacr.org/2016/770.pdf
+//! [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
 
-use crate::{bits_to_rate, keccakp::KeccakP, EncodedLen, Hasher, IntoXof, KeccakState, Xof};
+use crate::{
+    bits_to_rate,
+    checkpoint::{read_header, read_len, write_header, Algorithm},
+    keccakf_x4::hash_x4,
+    keccakp::{KeccakP, KeccakP14},
+    EncodedLen, Error, Hasher, IntoXof, KeccakState, Permutation, Xof,
+};
+use alloc::vec::Vec;
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 fn encode_len(len: usize) -> EncodedLen {
     let len_view = (len as u64).to_be_bytes();
@@ -14,44 +24,84 @@ fn encode_len(len: usize) -> EncodedLen {
     EncodedLen { offset, buffer }
 }
 
-/// The `KangarooTwelve` hash function defined [`here`].
-///
-/// # Usage
-///
-/// ```toml
-/// [dependencies]
-/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
-/// ```
-///
-/// [`here`]: https://eprint.iacr.org/2016/770.pdf
-#[derive(Clone)]
-pub struct KangarooTwelve<T> {
-    state: KeccakState<KeccakP>,
-    current_chunk: KeccakState<KeccakP>,
+/// Restores the state of the final node of an extendable-output function, that ends with one of
+/// the suffixes given by `KangarooTree::into_state`.
+fn restore_xof<P: Permutation, R: io::Read>(
+    reader: &mut R,
+    bits: u16,
+) -> io::Result<KeccakState<P>> {
+    let state = KeccakState::deserialize_with_delim(reader)?;
+    if state.rate != bits_to_rate(bits) || (state.delim != 0x06 && state.delim != 0x07) {
+        return Err(Error::InvalidState.into());
+    }
+    Ok(state)
+}
+
+struct ChainingValue {
+    state: [u8; 64],
+    size: usize,
+}
+
+impl ChainingValue {
+    fn security(bits: u16) -> ChainingValue {
+        ChainingValue {
+            state: [0u8; 64],
+            // 128 => 32, 256 => 64
+            size: bits as usize / 4,
+        }
+    }
+
+    #[inline]
+    fn as_bytes(&self) -> &[u8] {
+        &self.state[..self.size]
+    }
+
+    #[inline]
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.state[..self.size]
+    }
+}
+
+/// Chunking and final node logic shared by `KangarooTwelve` and `MarsupilamiFourteen`.
+struct KangarooTree<T, P> {
+    state: KeccakState<P>,
+    current_chunk: KeccakState<P>,
     custom_string: Option<T>,
+    bits: u16,
     written: usize,
     chunks: usize,
 }
 
-impl<T> KangarooTwelve<T> {
+impl<T: Clone, P> Clone for KangarooTree<T, P> {
+    fn clone(&self) -> Self {
+        KangarooTree {
+            state: self.state.clone(),
+            current_chunk: self.current_chunk.clone(),
+            custom_string: self.custom_string.clone(),
+            bits: self.bits,
+            written: self.written,
+            chunks: self.chunks,
+        }
+    }
+}
+
+impl<T, P: Permutation> KangarooTree<T, P> {
     const MAX_CHUNK_SIZE: usize = 8192;
 
-    /// Creates  new [`KangarooTwelve`] hasher with a security level of 128 bits.
-    ///
-    /// [`KangarooTwelve`]: struct.KangarooTwelve.html
-    pub fn new(custom_string: T) -> Self {
-        let rate = bits_to_rate(128);
-        KangarooTwelve {
+    fn new(custom_string: T, bits: u16) -> Self {
+        let rate = bits_to_rate(bits);
+        KangarooTree {
             state: KeccakState::new(rate, 0),
             current_chunk: KeccakState::new(rate, 0x0b),
             custom_string: Some(custom_string),
+            bits,
             written: 0,
             chunks: 0,
         }
     }
 }
 
-impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
+impl<T: AsRef<[u8]>, P: Permutation> KangarooTree<T, P> {
     fn update(&mut self, input: &[u8]) {
         let mut to_absorb = input;
         if self.chunks == 0 {
@@ -60,24 +110,42 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
             self.written += todo;
             to_absorb = &to_absorb[todo..];
 
-            if to_absorb.len() > 0 && self.written == Self::MAX_CHUNK_SIZE {
+            if !to_absorb.is_empty() && self.written == Self::MAX_CHUNK_SIZE {
                 self.state.update(&[0x03, 0, 0, 0, 0, 0, 0, 0]);
                 self.written = 0;
                 self.chunks += 1;
             }
         }
 
-        while to_absorb.len() > 0 {
+        while !to_absorb.is_empty() {
             if self.written == Self::MAX_CHUNK_SIZE {
-                let mut chunk_hash = [0u8; 32];
+                let mut chunk_hash = ChainingValue::security(self.bits);
                 let current_chunk = self.current_chunk.clone();
                 self.current_chunk.reset();
-                current_chunk.finalize(&mut chunk_hash);
-                self.state.update(&chunk_hash);
+                current_chunk.finalize(chunk_hash.as_bytes_mut());
+                self.state.update(chunk_hash.as_bytes());
                 self.written = 0;
                 self.chunks += 1;
             }
 
+            // whole chunks are hashed four at a time, as long as the last chunk is not one of them
+            if self.written == 0 {
+                let rate = bits_to_rate(self.bits) as usize;
+                let size = self.bits as usize / 4;
+                while to_absorb.len() > 4 * Self::MAX_CHUNK_SIZE {
+                    let (quad, rest) = to_absorb.split_at(4 * Self::MAX_CHUNK_SIZE);
+                    let (first, quad) = quad.split_at(Self::MAX_CHUNK_SIZE);
+                    let (second, quad) = quad.split_at(Self::MAX_CHUNK_SIZE);
+                    let (third, fourth) = quad.split_at(Self::MAX_CHUNK_SIZE);
+                    let chunk_hashes = hash_x4::<P>(rate, 0x0b, [first, second, third, fourth]);
+                    for chunk_hash in chunk_hashes.iter() {
+                        self.state.update(&chunk_hash[..size]);
+                    }
+                    self.chunks += 4;
+                    to_absorb = rest;
+                }
+            }
+
             let todo = core::cmp::min(Self::MAX_CHUNK_SIZE - self.written, to_absorb.len());
             self.current_chunk.update(&to_absorb[..todo]);
             self.written += todo;
@@ -85,6 +153,138 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
         }
     }
 
+    fn into_state(mut self) -> KeccakState<P> {
+        let custom_string = self
+            .custom_string
+            .take()
+            .expect("KangarooTwelve cannot be initialized without custom_string; qed");
+        let encoded_len = encode_len(custom_string.as_ref().len());
+        self.update(custom_string.as_ref());
+        self.update(encoded_len.value());
+
+        if self.chunks == 0 {
+            self.state.delim = 0x07;
+        } else {
+            let encoded_chunks = encode_len(self.chunks);
+            let mut tmp_chunk = ChainingValue::security(self.bits);
+            self.current_chunk.finalize(tmp_chunk.as_bytes_mut());
+            self.state.update(tmp_chunk.as_bytes());
+            self.state.update(encoded_chunks.value());
+            self.state.update(&[0xff, 0xff]);
+            self.state.delim = 0x06;
+        }
+
+        self.state
+    }
+
+    /// Writes a checkpoint: the header, the final node, the current chunk, the customization
+    /// string and the `written` and `chunks` counters.
+    fn checkpoint<W: io::Write>(&self, writer: &mut W, algorithm: Algorithm) -> io::Result<()> {
+        write_header(writer, algorithm, self.bits)?;
+        self.state.serialize(writer)?;
+        self.current_chunk.serialize(writer)?;
+        let custom_string = self.custom_string.as_ref().map_or(&[][..], AsRef::as_ref);
+        custom_string.serialize(writer)?;
+        (self.written as u64).serialize(writer)?;
+        (self.chunks as u64).serialize(writer)
+    }
+}
+
+impl<T: From<Vec<u8>>, P: Permutation> KangarooTree<T, P> {
+    /// Restores a checkpoint of `algorithm` with a security level of `bits` bits.
+    fn restore<R: io::Read>(reader: &mut R, algorithm: Algorithm, bits: u16) -> io::Result<Self> {
+        if read_header(reader, algorithm)? != bits {
+            return Err(Error::AlgorithmMismatch.into());
+        }
+        let mut state = KeccakState::deserialize_reader(reader)?;
+        let mut current_chunk = KeccakState::deserialize_reader(reader)?;
+        let rate = bits_to_rate(bits);
+        if state.rate != rate || current_chunk.rate != rate {
+            return Err(Error::InvalidState.into());
+        }
+        state.check_absorbing()?;
+        current_chunk.check_absorbing()?;
+        state.delim = 0;
+        current_chunk.delim = 0x0b;
+        let custom_string = Vec::<u8>::deserialize_reader(reader)?;
+        let written = read_len(reader)?;
+        if written > Self::MAX_CHUNK_SIZE {
+            return Err(Error::InvalidState.into());
+        }
+        let chunks = read_len(reader)?;
+        // the final node holds the first chunk, then a chaining value for each later chunk
+        // but the current one, which is absorbed into `current_chunk`
+        let rate = rate as usize;
+        let consistent = if chunks == 0 {
+            current_chunk.offset == 0 && state.offset as usize == written % rate
+        } else {
+            let size = bits as usize / 4;
+            let absorbed = (Self::MAX_CHUNK_SIZE + 8) % rate + (chunks - 1) % rate * size;
+            written != 0
+                && current_chunk.offset as usize == written % rate
+                && state.offset as usize == absorbed % rate
+        };
+        if !consistent {
+            return Err(Error::InvalidState.into());
+        }
+        Ok(KangarooTree {
+            state,
+            current_chunk,
+            custom_string: Some(custom_string.into()),
+            bits,
+            written,
+            chunks,
+        })
+    }
+}
+
+/// The `KangarooTwelve` hash function defined [`here`].
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
+/// ```
+///
+/// [`here`]: https://eprint.iacr.org/2016/770.pdf
+#[derive(Clone)]
+pub struct KangarooTwelve<T> {
+    tree: KangarooTree<T, KeccakP>,
+}
+
+// The checkpoint header is followed by the tree state; see `KangarooTree::checkpoint`. A
+// checkpoint is restored with any customization string type that can be built from a `Vec<u8>`.
+impl<T: AsRef<[u8]>> BorshSerialize for KangarooTwelve<T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.tree.checkpoint(writer, Algorithm::KangarooTwelve)
+    }
+}
+
+impl<T: From<Vec<u8>>> BorshDeserialize for KangarooTwelve<T> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(KangarooTwelve {
+            tree: KangarooTree::restore(reader, Algorithm::KangarooTwelve, 128)?,
+        })
+    }
+}
+
+impl<T> KangarooTwelve<T> {
+    /// Creates  new [`KangarooTwelve`] hasher with a security level of 128 bits.
+    ///
+    /// [`KangarooTwelve`]: struct.KangarooTwelve.html
+    pub fn new(custom_string: T) -> Self {
+        KangarooTwelve {
+            tree: KangarooTree::new(custom_string, 128),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
+    fn update(&mut self, input: &[u8]) {
+        self.tree.update(input);
+    }
+
     fn finalize(self, output: &mut [u8]) {
         let mut xof = self.into_xof();
         xof.squeeze(output);
@@ -103,7 +303,7 @@ impl<T: AsRef<[u8]>> Hasher for KangarooTwelve<T> {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::{KangarooTwelve, Xof, IntoXof, Hasher};
+/// # use tari_tiny_keccak::{KangarooTwelve, Xof, IntoXof, Hasher};
 /// let input = b"hello world";
 /// let mut output = [0u8; 64];
 /// let mut hasher = KangarooTwelve::new(b"");
@@ -125,35 +325,283 @@ pub struct KangarooTwelveXof {
     state: KeccakState<KeccakP>,
 }
 
+// The checkpoint header is followed by the state and the suffix of the final node.
+impl BorshSerialize for KangarooTwelveXof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::KangarooTwelveXof, 128)?;
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for KangarooTwelveXof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        if read_header(reader, Algorithm::KangarooTwelveXof)? != 128 {
+            return Err(Error::AlgorithmMismatch.into());
+        }
+        Ok(KangarooTwelveXof {
+            state: restore_xof(reader, 128)?,
+        })
+    }
+}
+
 impl<T: AsRef<[u8]>> IntoXof for KangarooTwelve<T> {
     type Xof = KangarooTwelveXof;
 
-    fn into_xof(mut self) -> KangarooTwelveXof {
-        let custom_string = self
-            .custom_string
-            .take()
-            .expect("KangarooTwelve cannot be initialized without custom_string; qed");
-        let encoded_len = encode_len(custom_string.as_ref().len());
-        self.update(custom_string.as_ref());
-        self.update(encoded_len.value());
+    fn into_xof(self) -> KangarooTwelveXof {
+        KangarooTwelveXof {
+            state: self.tree.into_state(),
+        }
+    }
+}
 
-        if self.chunks == 0 {
-            self.state.delim = 0x07;
-        } else {
-            let encoded_chunks = encode_len(self.chunks);
-            let mut tmp_chunk = [0u8; 32];
-            self.current_chunk.finalize(&mut tmp_chunk);
-            self.state.update(&tmp_chunk);
-            self.state.update(encoded_chunks.value());
-            self.state.update(&[0xff, 0xff]);
-            self.state.delim = 0x06;
+impl Xof for KangarooTwelveXof {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        self.state.squeeze(output);
+    }
+}
+
+/// The `KT256` hash function defined in [`RFC 9861`].
+///
+/// `KT256` is the 256-bit security level variant of [`KangarooTwelve`]. It uses the same tree
+/// structure, but with the rate of `TurboSHAKE256` and 64-byte chaining values.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
+/// ```
+///
+/// [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
+/// [`KangarooTwelve`]: struct.KangarooTwelve.html
+#[derive(Clone)]
+pub struct KangarooTwelve256<T> {
+    tree: KangarooTree<T, KeccakP>,
+}
+
+impl<T: AsRef<[u8]>> BorshSerialize for KangarooTwelve256<T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.tree.checkpoint(writer, Algorithm::KangarooTwelve256)
+    }
+}
+
+impl<T: From<Vec<u8>>> BorshDeserialize for KangarooTwelve256<T> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(KangarooTwelve256 {
+            tree: KangarooTree::restore(reader, Algorithm::KangarooTwelve256, 256)?,
+        })
+    }
+}
+
+impl<T> KangarooTwelve256<T> {
+    /// Creates  new [`KangarooTwelve256`] hasher with a security level of 256 bits.
+    ///
+    /// [`KangarooTwelve256`]: struct.KangarooTwelve256.html
+    pub fn new(custom_string: T) -> Self {
+        KangarooTwelve256 {
+            tree: KangarooTree::new(custom_string, 256),
         }
+    }
+}
 
-        KangarooTwelveXof { state: self.state }
+impl<T: AsRef<[u8]>> Hasher for KangarooTwelve256<T> {
+    fn update(&mut self, input: &[u8]) {
+        self.tree.update(input);
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        let mut xof = self.into_xof();
+        xof.squeeze(output);
     }
 }
 
-impl Xof for KangarooTwelveXof {
+/// The `KT256` extendable-output function defined in [`RFC 9861`].
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{KangarooTwelve256, Xof, IntoXof, Hasher};
+/// let input = b"hello world";
+/// let mut output = [0u8; 128];
+/// let mut hasher = KangarooTwelve256::new(b"");
+/// hasher.update(input);
+/// let mut xof = hasher.into_xof();
+/// xof.squeeze(&mut output[..64]);
+/// xof.squeeze(&mut output[64..]);
+/// ```
+///
+/// ---
+///
+/// [`KangarooTwelve256Xof`] can be created only by using [`KangarooTwelve256::IntoXof`] interface.
+///
+/// [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
+/// [`KangarooTwelve256Xof`]: struct.KangarooTwelve256Xof.html
+/// [`KangarooTwelve256::IntoXof`]: struct.KangarooTwelve256.html#impl-IntoXof
+#[derive(Clone)]
+pub struct KangarooTwelve256Xof {
+    state: KeccakState<KeccakP>,
+}
+
+impl BorshSerialize for KangarooTwelve256Xof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::KangarooTwelve256Xof, 256)?;
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for KangarooTwelve256Xof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        if read_header(reader, Algorithm::KangarooTwelve256Xof)? != 256 {
+            return Err(Error::AlgorithmMismatch.into());
+        }
+        Ok(KangarooTwelve256Xof {
+            state: restore_xof(reader, 256)?,
+        })
+    }
+}
+
+impl<T: AsRef<[u8]>> IntoXof for KangarooTwelve256<T> {
+    type Xof = KangarooTwelve256Xof;
+
+    fn into_xof(self) -> KangarooTwelve256Xof {
+        KangarooTwelve256Xof {
+            state: self.tree.into_state(),
+        }
+    }
+}
+
+impl Xof for KangarooTwelve256Xof {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        self.state.squeeze(output);
+    }
+}
+
+/// The `MarsupilamiFourteen` hash function defined [`here`].
+///
+/// `MarsupilamiFourteen` is a sibling of [`KangarooTwelve`] with a larger safety margin. It uses
+/// `keccak-p[1600, 14]`, a security level of 256 bits and 64-byte chaining values.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
+/// ```
+///
+/// [`here`]: https://eprint.iacr.org/2016/770.pdf
+/// [`KangarooTwelve`]: struct.KangarooTwelve.html
+#[derive(Clone)]
+pub struct MarsupilamiFourteen<T> {
+    tree: KangarooTree<T, KeccakP14>,
+}
+
+impl<T: AsRef<[u8]>> BorshSerialize for MarsupilamiFourteen<T> {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.tree.checkpoint(writer, Algorithm::MarsupilamiFourteen)
+    }
+}
+
+impl<T: From<Vec<u8>>> BorshDeserialize for MarsupilamiFourteen<T> {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(MarsupilamiFourteen {
+            tree: KangarooTree::restore(reader, Algorithm::MarsupilamiFourteen, 256)?,
+        })
+    }
+}
+
+impl<T> MarsupilamiFourteen<T> {
+    /// Creates  new [`MarsupilamiFourteen`] hasher with a security level of 256 bits.
+    ///
+    /// [`MarsupilamiFourteen`]: struct.MarsupilamiFourteen.html
+    pub fn new(custom_string: T) -> Self {
+        MarsupilamiFourteen {
+            tree: KangarooTree::new(custom_string, 256),
+        }
+    }
+}
+
+impl<T: AsRef<[u8]>> Hasher for MarsupilamiFourteen<T> {
+    fn update(&mut self, input: &[u8]) {
+        self.tree.update(input);
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        let mut xof = self.into_xof();
+        xof.squeeze(output);
+    }
+}
+
+/// The `MarsupilamiFourteen` extendable-output function defined [`here`].
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["k12"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{MarsupilamiFourteen, Xof, IntoXof, Hasher};
+/// let input = b"hello world";
+/// let mut output = [0u8; 128];
+/// let mut hasher = MarsupilamiFourteen::new(b"");
+/// hasher.update(input);
+/// let mut xof = hasher.into_xof();
+/// xof.squeeze(&mut output[..64]);
+/// xof.squeeze(&mut output[64..]);
+/// ```
+///
+/// ---
+///
+/// [`MarsupilamiFourteenXof`] can be created only by using [`MarsupilamiFourteen::IntoXof`]
+/// interface.
+///
+/// [`here`]: https://eprint.iacr.org/2016/770.pdf
+/// [`MarsupilamiFourteenXof`]: struct.MarsupilamiFourteenXof.html
+/// [`MarsupilamiFourteen::IntoXof`]: struct.MarsupilamiFourteen.html#impl-IntoXof
+#[derive(Clone)]
+pub struct MarsupilamiFourteenXof {
+    state: KeccakState<KeccakP14>,
+}
+
+impl BorshSerialize for MarsupilamiFourteenXof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::MarsupilamiFourteenXof, 256)?;
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for MarsupilamiFourteenXof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        if read_header(reader, Algorithm::MarsupilamiFourteenXof)? != 256 {
+            return Err(Error::AlgorithmMismatch.into());
+        }
+        Ok(MarsupilamiFourteenXof {
+            state: restore_xof(reader, 256)?,
+        })
+    }
+}
+
+impl<T: AsRef<[u8]>> IntoXof for MarsupilamiFourteen<T> {
+    type Xof = MarsupilamiFourteenXof;
+
+    fn into_xof(self) -> MarsupilamiFourteenXof {
+        MarsupilamiFourteenXof {
+            state: self.tree.into_state(),
+        }
+    }
+}
+
+impl Xof for MarsupilamiFourteenXof {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
@@ -1,9 +1,10 @@
 //! The `Keccak` hash functions.
 
-use super::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
-#[cfg(test)]
-use super::{Buffer, Mode};
-use borsh::{BorshDeserialize, BorshSerialize};
+use super::{
+    hash_short, keccakf::KeccakF, or_panic, security_level_to_rate, Error, Hasher, KeccakState,
+    Permutation, STATE_BYTES,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 /// The `Keccak` hash functions defined in [`Keccak SHA3 submission`].
 ///
 /// # Usage
@@ -14,14 +15,27 @@ use borsh::{BorshDeserialize, BorshSerialize};
 /// ```
 ///
 /// [`Keccak SHA3 submission`]: https://keccak.team/files/Keccak-submission-3.pdf
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
-pub struct Keccak {
-    state: KeccakState<KeccakF>,
+#[derive(Clone, Debug)]
+pub struct Keccak<P = KeccakF> {
+    state: KeccakState<P>,
 }
 
-impl Keccak {
-    const DELIM: u8 = 0x01;
+// Implemented for the default permutation only, so that `Keccak::deserialize` keeps inferring it.
+impl BorshSerialize for Keccak {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Keccak {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Keccak::from_state(KeccakState::deserialize_reader(
+            reader,
+        )?)?)
+    }
+}
 
+impl Keccak {
     /// Creates  new [`Keccak`] hasher with a security level of 224 bits.
     ///
     /// [`Keccak`]: struct.Keccak.html
@@ -36,14 +50,6 @@ impl Keccak {
         Keccak::new(256)
     }
 
-    #[cfg(test)]
-    /// Creates a new [`Keccak`] hasher with the following state
-    pub fn new_with(buffer: Buffer, offset: u8, rate: u8, mode: Mode) -> Keccak {
-        Keccak {
-            state: KeccakState::new_with(buffer, offset, rate, Self::DELIM, mode),
-        }
-    }
-
     /// Creates  new [`Keccak`] hasher with a security level of 384 bits.
     ///
     /// [`Keccak`]: struct.Keccak.html
@@ -57,21 +63,77 @@ impl Keccak {
     pub fn v512() -> Keccak {
         Keccak::new(512)
     }
+}
+
+impl<P: Permutation> Keccak<P> {
+    const DELIM: u8 = 0x01;
+
+    /// Creates new [`Keccak`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 224, 256, 384 or 512.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    pub fn new(bits: u16) -> Keccak<P> {
+        or_panic(Self::try_new(bits))
+    }
+
+    /// Creates new [`Keccak`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 224, 256, 384 or 512.
+    ///
+    /// [`Keccak`]: struct.Keccak.html
+    pub fn try_new(bits: u16) -> Result<Keccak<P>, Error> {
+        let rate = security_level_to_rate(bits, &[224, 256, 384, 512])?;
+        Ok(Keccak {
+            state: KeccakState::new(rate, Self::DELIM),
+        })
+    }
+
+    /// Encodes the state of the hasher in the layout described in [`STATE_BYTES`].
+    ///
+    /// [`STATE_BYTES`]: constant.STATE_BYTES.html
+    pub fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        self.state.to_bytes()
+    }
 
-    fn new(bits: u16) -> Keccak {
-        Keccak {
-            state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
-        }
+    /// Decodes a state encoded by [`to_bytes`]. Fails with [`Error::InvalidState`] if `bytes`
+    /// is not the state of a [`Keccak`] hasher.
+    ///
+    /// [`to_bytes`]: struct.Keccak.html#method.to_bytes
+    /// [`Keccak`]: struct.Keccak.html
+    /// [`Error::InvalidState`]: enum.Error.html#variant.InvalidState
+    pub fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<Keccak<P>, Error> {
+        Self::from_state(KeccakState::from_bytes(bytes)?)
+    }
+
+    /// Wraps a decoded state, see `KeccakState::validate`.
+    fn from_state(state: KeccakState<P>) -> Result<Keccak<P>, Error> {
+        state.validate(&[224, 256, 384, 512], |delim| delim == Self::DELIM)?;
+        Ok(Keccak { state })
+    }
+
+    /// Absorbs the first `bit_len` bits of `input`, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is shorter than `bit_len` bits, or if trailing bits that don't fill a
+    /// byte have already been absorbed.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        self.state.update_bits(input, bit_len);
     }
 }
 
-impl Hasher for Keccak {
+impl<P: Permutation> Hasher for Keccak<P> {
     /// Absorb additional input. Can be called multiple times.
     ///
     /// # Example
     ///
     /// ```
-    /// # use tiny_keccak::{Hasher, Keccak};
+    /// # use tari_tiny_keccak::{Hasher, Keccak};
     /// #
     /// # fn main() {
     /// # let mut keccak = Keccak::v256();
@@ -88,7 +150,7 @@ impl Hasher for Keccak {
     /// # Example
     ///
     /// ```
-    /// # use tiny_keccak::{Hasher, Keccak};
+    /// # use tari_tiny_keccak::{Hasher, Keccak};
     /// #
     /// # fn main() {
     /// # let keccak = Keccak::v256();
@@ -101,3 +163,36 @@ impl Hasher for Keccak {
         self.state.finalize(output);
     }
 }
+
+/// Computes Keccak-256 of `input` with a single permutation. Faster than [`Keccak::v256`] for keys,
+/// digests and other short messages of a fixed length.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["keccak"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{keccak256_short, Hasher, Keccak};
+/// let mut output = [0u8; 32];
+/// let mut keccak = Keccak::v256();
+/// keccak.update(b"hello world");
+/// keccak.finalize(&mut output);
+/// assert_eq!(keccak256_short(b"hello world"), output);
+/// ```
+///
+/// The input must be shorter than the rate of 136 bytes, or the call doesn't compile:
+///
+/// ```compile_fail
+/// # use tari_tiny_keccak::keccak256_short;
+/// keccak256_short(&[0u8; 136]);
+/// ```
+///
+/// [`Keccak::v256`]: struct.Keccak.html#method.v256
+pub fn keccak256_short<const N: usize>(input: &[u8; N]) -> [u8; 32] {
+    hash_short::<N, 136, 32>(input, Keccak::<KeccakF>::DELIM)
+}
@@ -2,7 +2,7 @@ use crate::{Buffer, Permutation};
 
 const ROUNDS: usize = 24;
 
-const RC: [u64; ROUNDS] = [
+pub(crate) const RC: [u64; ROUNDS] = [
     1u64,
     0x8082u64,
     0x800000000000808au64,
@@ -31,11 +31,89 @@ const RC: [u64; ROUNDS] = [
 
 keccak_function!("`keccak-f[1600, 24]`", keccakf, ROUNDS, RC);
 
-#[derive(Debug)]
+keccak_function!(
+    "`keccak-p[1600, n_r]`, where `n_r` is given by `rounds`. Applies the last `rounds` rounds \
+    of `keccak-f[1600, 24]`.\n\n# Panics\n\nPanics if `rounds` is greater than 24.",
+    keccak_p,
+    RC
+);
+
+/// The `keccak-f[1600, 24]` permutation, used by default by the hashers of the `FIPS-202` and
+/// `SP800-185` families.
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "sponge",
+    feature = "duplex"
+))]
+#[derive(Clone, Copy, Debug)]
 pub struct KeccakF;
 
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "kmac",
+    feature = "tuple_hash",
+    feature = "parallel_hash",
+    feature = "sponge",
+    feature = "duplex"
+))]
 impl Permutation for KeccakF {
+    const COMPLEMENTED: bool = crate::LANE_COMPLEMENTING;
+
     fn execute(buffer: &mut Buffer) {
         keccakf(buffer.words());
     }
+
+    fn execute_complemented(buffer: &mut Buffer) {
+        crate::keccak_rounds_complemented(buffer.words(), &RC);
+    }
+
+    fn execute_x4(states: &mut [[u64; crate::WORDS]; 4]) {
+        crate::keccakf_x4(states);
+    }
+}
+
+/// `keccak-p[1600, ROUNDS]` permutation with the number of rounds chosen at compile time.
+///
+/// `KeccakPRounds<24>` is equivalent to `keccak-f[1600]`, `KeccakPRounds<12>` is the permutation
+/// used by `KangarooTwelve` and `TurboSHAKE`. `ROUNDS` cannot exceed 24, a greater number of
+/// rounds fails to compile:
+///
+/// ```compile_fail
+/// use tari_tiny_keccak::{Buffer, KeccakPRounds, Permutation};
+///
+/// KeccakPRounds::<25>::execute(&mut Buffer::default());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakPRounds<const ROUNDS: usize>;
+
+impl<const ROUNDS: usize> KeccakPRounds<ROUNDS> {
+    const VALID_ROUNDS: () = assert!(ROUNDS <= RC.len(), "number of rounds cannot exceed 24");
+}
+
+impl<const ROUNDS: usize> Permutation for KeccakPRounds<ROUNDS> {
+    const COMPLEMENTED: bool = crate::LANE_COMPLEMENTING;
+
+    fn execute(buffer: &mut Buffer) {
+        let () = Self::VALID_ROUNDS;
+        keccak_p(buffer.words(), ROUNDS);
+    }
+
+    fn execute_complemented(buffer: &mut Buffer) {
+        let () = Self::VALID_ROUNDS;
+        crate::keccak_rounds_complemented(buffer.words(), &RC[RC.len() - ROUNDS..]);
+    }
+
+    fn execute_x4(states: &mut [[u64; crate::WORDS]; 4]) {
+        let () = Self::VALID_ROUNDS;
+        crate::keccakf_x4::keccak_p_x4(states, ROUNDS);
+    }
 }
@@ -0,0 +1,237 @@
+//! Four-way parallel `keccak-p[1600]` permutations.
+//!
+//! On `x86_64` the four states are permuted at once with AVX2 instructions. AVX2 support is
+//! detected at runtime when the `std` feature is enabled, and at compile time otherwise. When it
+//! is not available, every state is permuted separately.
+
+use crate::{keccakf, WORDS};
+
+/// `keccak-f[1600, 24]` applied to four independent states.
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{keccakf, keccakf_x4};
+/// let mut states = [[0u64; 25], [1u64; 25], [2u64; 25], [3u64; 25]];
+/// let mut expected = states;
+/// keccakf_x4(&mut states);
+/// for state in expected.iter_mut() {
+///     keccakf(state);
+/// }
+/// assert_eq!(expected, states);
+/// ```
+pub fn keccakf_x4(states: &mut [[u64; WORDS]; 4]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2::is_available() {
+            // SAFETY: AVX2 is available, the only requirement of `avx2::keccak_p_x4`.
+            unsafe { avx2::keccak_p_x4(states, 24) };
+            return;
+        }
+    }
+
+    for state in states.iter_mut() {
+        keccakf(state);
+    }
+}
+
+/// `keccak-p[1600, rounds]` applied to four independent states.
+pub(crate) fn keccak_p_x4(states: &mut [[u64; WORDS]; 4], rounds: usize) {
+    assert!(rounds <= 24, "number of rounds cannot exceed 24");
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2::is_available() {
+            // SAFETY: AVX2 is available, the only requirement of `avx2::keccak_p_x4`.
+            unsafe { avx2::keccak_p_x4(states, rounds) };
+            return;
+        }
+    }
+
+    for state in states.iter_mut() {
+        crate::keccak_p(state, rounds);
+    }
+}
+
+/// Absorbs four messages of equal length into four fresh sponges and squeezes the first 64 bytes
+/// of each of them. The rate must not be smaller than 64 bytes.
+#[cfg(any(feature = "k12", feature = "parallel_hash"))]
+pub(crate) fn hash_x4<P: crate::Permutation>(
+    rate: usize,
+    delim: u8,
+    inputs: [&[u8]; 4],
+) -> [[u8; 64]; 4] {
+    let len = inputs[0].len();
+    debug_assert!(inputs.iter().all(|input| input.len() == len));
+
+    let mut states = [[0u64; WORDS]; 4];
+    let full_blocks = len / rate * rate;
+    for block in (0..full_blocks).step_by(rate) {
+        for (state, input) in states.iter_mut().zip(inputs.iter()) {
+            xorin(state, &input[block..block + rate]);
+        }
+        P::execute_x4(&mut states);
+    }
+
+    for (state, input) in states.iter_mut().zip(inputs.iter()) {
+        let mut last = [0u8; WORDS * 8];
+        let tail = &input[full_blocks..];
+        last[..tail.len()].copy_from_slice(tail);
+        last[tail.len()] ^= delim;
+        last[rate - 1] ^= 0x80;
+        xorin(state, &last[..rate]);
+    }
+    P::execute_x4(&mut states);
+
+    let mut outputs = [[0u8; 64]; 4];
+    for (state, output) in states.iter().zip(outputs.iter_mut()) {
+        for (lane, bytes) in state.iter().zip(output.chunks_exact_mut(8)) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+    }
+    outputs
+}
+
+#[cfg(any(feature = "k12", feature = "parallel_hash"))]
+fn xorin(state: &mut [u64; WORDS], block: &[u8]) {
+    use core::convert::TryInto;
+
+    for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+        *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use crate::{keccakf::RC, WORDS};
+    use core::arch::x86_64::*;
+    use core::ops::{BitAnd, BitXor, BitXorAssign, Not};
+
+    /// The same lane of four different states.
+    ///
+    /// The methods of `Lanes` call AVX2 intrinsics without checking that AVX2 is available. This
+    /// is sound because `Lanes` is private to this module and only constructed and used inside
+    /// `keccak_p_x4`, which enables AVX2 and whose callers check that the CPU supports it. Every
+    /// method is `#[inline(always)]` so that it is compiled as part of `keccak_p_x4`.
+    #[derive(Clone, Copy)]
+    struct Lanes(__m256i);
+
+    impl Lanes {
+        #[inline(always)]
+        fn splat(value: u64) -> Lanes {
+            // SAFETY: `Lanes` only exists inside `keccak_p_x4`, where AVX2 is available.
+            Lanes(unsafe { _mm256_set1_epi64x(value as i64) })
+        }
+
+        #[inline(always)]
+        fn rotate_left(self, n: u32) -> Lanes {
+            // SAFETY: `Lanes` only exists inside `keccak_p_x4`, where AVX2 is available.
+            unsafe {
+                let left = _mm256_set1_epi64x(i64::from(n));
+                let right = _mm256_set1_epi64x(64 - i64::from(n));
+                Lanes(_mm256_or_si256(
+                    _mm256_sllv_epi64(self.0, left),
+                    _mm256_srlv_epi64(self.0, right),
+                ))
+            }
+        }
+    }
+
+    impl Default for Lanes {
+        #[inline(always)]
+        fn default() -> Lanes {
+            // SAFETY: `Lanes` only exists inside `keccak_p_x4`, where AVX2 is available.
+            Lanes(unsafe { _mm256_setzero_si256() })
+        }
+    }
+
+    impl BitXor for Lanes {
+        type Output = Lanes;
+
+        #[inline(always)]
+        fn bitxor(self, rhs: Lanes) -> Lanes {
+            // SAFETY: `Lanes` only exists inside `keccak_p_x4`, where AVX2 is available.
+            Lanes(unsafe { _mm256_xor_si256(self.0, rhs.0) })
+        }
+    }
+
+    impl BitXorAssign for Lanes {
+        #[inline(always)]
+        fn bitxor_assign(&mut self, rhs: Lanes) {
+            *self = *self ^ rhs;
+        }
+    }
+
+    impl BitAnd for Lanes {
+        type Output = Lanes;
+
+        #[inline(always)]
+        fn bitand(self, rhs: Lanes) -> Lanes {
+            // SAFETY: `Lanes` only exists inside `keccak_p_x4`, where AVX2 is available.
+            Lanes(unsafe { _mm256_and_si256(self.0, rhs.0) })
+        }
+    }
+
+    impl Not for Lanes {
+        type Output = Lanes;
+
+        #[inline(always)]
+        fn not(self) -> Lanes {
+            self ^ Lanes::splat(u64::MAX)
+        }
+    }
+
+    #[inline]
+    pub fn is_available() -> bool {
+        #[cfg(target_feature = "avx2")]
+        {
+            true
+        }
+
+        #[cfg(all(not(target_feature = "avx2"), feature = "std"))]
+        {
+            std::is_x86_feature_detected!("avx2")
+        }
+
+        #[cfg(all(not(target_feature = "avx2"), not(feature = "std")))]
+        {
+            false
+        }
+    }
+
+    /// `keccak-p[1600, rounds]` applied to four states at once.
+    ///
+    /// # Safety
+    ///
+    /// The CPU must support AVX2, see `is_available`.
+    #[target_feature(enable = "avx2")]
+    #[allow(unused_assignments)]
+    pub unsafe fn keccak_p_x4(states: &mut [[u64; WORDS]; 4], rounds: usize) {
+        let mut a = [Lanes::default(); WORDS];
+        for (i, lanes) in a.iter_mut().enumerate() {
+            *lanes = Lanes(_mm256_set_epi64x(
+                states[3][i] as i64,
+                states[2][i] as i64,
+                states[1][i] as i64,
+                states[0][i] as i64,
+            ));
+        }
+
+        let mut rc = [Lanes::default(); 24];
+        for (lanes, rc) in rc.iter_mut().zip(RC.iter()) {
+            *lanes = Lanes::splat(*rc);
+        }
+
+        keccak_function!(@rounds a, Lanes, Lanes::rotate_left, rc[24 - rounds..]);
+
+        for (i, lanes) in a.iter().enumerate() {
+            let mut out = [0u64; 4];
+            // SAFETY: `out` is 32 bytes long, the size of a `__m256i`, and `storeu` doesn't need
+            // it to be aligned.
+            _mm256_storeu_si256(out.as_mut_ptr() as *mut __m256i, lanes.0);
+            for (state, lane) in states.iter_mut().zip(out.iter()) {
+                state[i] = *lane;
+            }
+        }
+    }
+}
@@ -2,27 +2,74 @@ use crate::{Buffer, Permutation};
 
 const ROUNDS: usize = 12;
 
-const RC: [u64; ROUNDS] = [
-    0x000000008000808b,
-    0x800000000000008b,
-    0x8000000000008089,
-    0x8000000000008003,
-    0x8000000000008002,
-    0x8000000000000080,
-    0x000000000000800a,
-    0x800000008000000a,
-    0x8000000080008081,
-    0x8000000000008080,
-    0x0000000080000001,
-    0x8000000080008008,
-];
-
-keccak_function!("`keccak-p[1600, 12]`", keccakp, ROUNDS, RC);
+const ROUNDS14: usize = 14;
+
+// the last 12 rounds of `keccak-f[1600, 24]`
+keccak_function!(
+    "`keccak-p[1600, 12]`",
+    keccakp,
+    ROUNDS,
+    crate::keccakf::RC[24 - ROUNDS..]
+);
+
+// the last 14 rounds of `keccak-f[1600, 24]`
+keccak_function!(
+    "`keccak-p[1600, 14]`",
+    keccakp14,
+    ROUNDS14,
+    crate::keccakf::RC[24 - ROUNDS14..]
+);
+
+/// `keccak-p[1600, 12]` that calls `trace` with the round number, the step and the state after
+/// every step of every round. Rounds are numbered from 12 to 23, see [`keccak_p_traced`].
+///
+/// [`keccak_p_traced`]: fn.keccak_p_traced.html
+pub fn keccakp_traced<F: FnMut(usize, crate::Step, &[u64; crate::WORDS])>(
+    a: &mut [u64; crate::WORDS],
+    trace: F,
+) {
+    crate::keccak_p_traced(a, ROUNDS, trace);
+}
+
+/// Inverse of `keccak-p[1600, 12]`.
+pub fn keccakp_inverse(a: &mut [u64; crate::WORDS]) {
+    crate::keccak_p_inverse(a, ROUNDS);
+}
 
 pub struct KeccakP;
 
 impl Permutation for KeccakP {
+    const COMPLEMENTED: bool = crate::LANE_COMPLEMENTING;
+
     fn execute(buffer: &mut Buffer) {
         keccakp(buffer.words());
     }
+
+    fn execute_complemented(buffer: &mut Buffer) {
+        crate::keccak_rounds_complemented(buffer.words(), &crate::keccakf::RC[24 - ROUNDS..]);
+    }
+
+    fn execute_x4(states: &mut [[u64; crate::WORDS]; 4]) {
+        crate::keccakf_x4::keccak_p_x4(states, ROUNDS);
+    }
+}
+
+#[cfg(feature = "k12")]
+pub struct KeccakP14;
+
+#[cfg(feature = "k12")]
+impl Permutation for KeccakP14 {
+    const COMPLEMENTED: bool = crate::LANE_COMPLEMENTING;
+
+    fn execute(buffer: &mut Buffer) {
+        keccakp14(buffer.words());
+    }
+
+    fn execute_complemented(buffer: &mut Buffer) {
+        crate::keccak_rounds_complemented(buffer.words(), &crate::keccakf::RC[24 - ROUNDS14..]);
+    }
+
+    fn execute_x4(states: &mut [[u64; crate::WORDS]; 4]) {
+        crate::keccakf_x4::keccak_p_x4(states, ROUNDS14);
+    }
 }
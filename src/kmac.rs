@@ -1,4 +1,10 @@
-use crate::{bits_to_rate, left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::{
+    bits_to_rate,
+    checkpoint::{read_header, write_header, Algorithm},
+    keccakf::KeccakF,
+    left_encode, or_panic, right_encode, CShake, Error, Hasher, IntoXof, Permutation, Xof,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 /// The `KMAC` pseudo-random functions defined in [`SP800-185`].
 ///
@@ -15,7 +21,16 @@ use crate::{bits_to_rate, left_encode, right_encode, CShake, Hasher, IntoXof, Xo
 /// tiny-keccak = { version = "2.0.0", features = ["kmac"] }
 /// ```
 ///
+/// # Checkpoints
+///
+/// The Borsh and `serde` encodings of a [`Kmac`] hold its sponge state, which has absorbed the
+/// key. Anyone who holds a checkpoint can compute the MAC of any message that extends the one it
+/// was taken after, so a checkpoint must be stored and sent with the same care as the key itself.
+/// The same goes for [`KmacXof`].
+///
 /// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+/// [`Kmac`]: struct.Kmac.html
+/// [`KmacXof`]: struct.KmacXof.html
 /// [`KMAC128`]: struct.Kmac.html#method.v128
 /// [`KMAC256`]: struct.Kmac.html#method.v256
 /// [`SHAKE`]: struct.Shake.html
@@ -23,8 +38,27 @@ use crate::{bits_to_rate, left_encode, right_encode, CShake, Hasher, IntoXof, Xo
 /// [`cSHAKE128`]: struct.CShake.html#method.v128
 /// [`cSHAKE256`]: struct.CShake.html#method.v256
 #[derive(Clone)]
-pub struct Kmac {
-    state: CShake,
+pub struct Kmac<P = KeccakF> {
+    state: CShake<P>,
+}
+
+// Implemented for the default permutation only, like `CShake`. The `cSHAKE` state follows a
+// checkpoint header, see the `checkpoint` module.
+impl BorshSerialize for Kmac {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::Kmac, self.state.bits())?;
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Kmac {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::Kmac)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        state.check_absorbing()?;
+        Ok(Kmac { state })
+    }
 }
 
 impl Kmac {
@@ -41,19 +75,37 @@ impl Kmac {
     pub fn v256(key: &[u8], custom_string: &[u8]) -> Kmac {
         Kmac::new(key, custom_string, 256)
     }
+}
+
+impl<P: Permutation> Kmac<P> {
+    /// Creates new [`Kmac`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 128 or 256.
+    ///
+    /// [`Kmac`]: struct.Kmac.html
+    pub fn new(key: &[u8], custom_string: &[u8], bits: u16) -> Kmac<P> {
+        or_panic(Self::try_new(key, custom_string, bits))
+    }
 
-    fn new(key: &[u8], custom_string: &[u8], bits: u16) -> Kmac {
+    /// Creates new [`Kmac`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 128 or 256.
+    ///
+    /// [`Kmac`]: struct.Kmac.html
+    pub fn try_new(key: &[u8], custom_string: &[u8], bits: u16) -> Result<Kmac<P>, Error> {
+        let mut state = CShake::try_new(b"KMAC", custom_string, bits)?;
         let rate = bits_to_rate(bits);
-        let mut state = CShake::new(b"KMAC", custom_string, bits as u16);
         state.update(left_encode(rate as usize).value());
         state.update(left_encode(key.len() * 8).value());
         state.update(key);
-        state.fill_block();
-        Kmac { state }
+        state.bytepad();
+        Ok(Kmac { state })
     }
 }
 
-impl Hasher for Kmac {
+impl<P: Permutation> Hasher for Kmac<P> {
     fn update(&mut self, input: &[u8]) {
         self.state.update(input)
     }
@@ -76,7 +128,7 @@ impl Hasher for Kmac {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::{Kmac, Xof, IntoXof, Hasher};
+/// # use tari_tiny_keccak::{Kmac, Xof, IntoXof, Hasher};
 /// let input = b"hello world";
 /// let mut output = [0u8; 64];
 /// let mut kmac = Kmac::v256(b"", b"");
@@ -94,12 +146,28 @@ impl Hasher for Kmac {
 /// [`KmacXof`]: struct.KmacXof.html
 /// [`Kmac::IntoXof`]: struct.Kmac.html#impl-IntoXof
 #[derive(Clone)]
-pub struct KmacXof {
-    state: CShake,
+pub struct KmacXof<P = KeccakF> {
+    state: CShake<P>,
+}
+
+impl BorshSerialize for KmacXof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::KmacXof, self.state.bits())?;
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for KmacXof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::KmacXof)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        Ok(KmacXof { state })
+    }
 }
 
-impl IntoXof for Kmac {
-    type Xof = KmacXof;
+impl<P: Permutation> IntoXof for Kmac<P> {
+    type Xof = KmacXof<P>;
 
     fn into_xof(mut self) -> Self::Xof {
         self.state.update(right_encode(0).value());
@@ -107,7 +175,7 @@ impl IntoXof for Kmac {
     }
 }
 
-impl Xof for KmacXof {
+impl<P: Permutation> Xof for KmacXof<P> {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
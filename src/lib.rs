@@ -1,9 +1,10 @@
-//! Keccak derived functions specified in [`FIPS-202`], [`SP800-185`] and [`KangarooTwelve`].
+//! Keccak derived functions specified in [`FIPS-202`], [`SP800-185`], [`KangarooTwelve`] and
+//! [`RFC 9861`].
 //!
 //! # Example
 //!
 //! ```
-//! # use tiny_keccak::Hasher;
+//! # use tari_tiny_keccak::Hasher;
 //! #
 //! # fn foo<H: Hasher>(mut hasher: H) {
 //! let input_a = b"hello world";
@@ -15,6 +16,22 @@
 //! # }
 //! ```
 //!
+//! # Bit-oriented messages
+//!
+//! `Keccak`, `Sha3`, `Shake` and `CShake` absorb messages that are not a whole number of bytes
+//! with `update_bits`, and `Shake` and `CShake` squeeze outputs of any number of bits with
+//! `squeeze_bits`. Bits are read and written from the least significant bit of every byte, as in
+//! Appendix B.1 of [`FIPS-202`], so the last byte of a message that is not a whole number of
+//! bytes holds its bits in its lowest bits.
+//!
+//! Trailing bits that don't fill a byte end the message. After them, `update` and `update_bits`
+//! panic and only the output can be read, also from a state restored from a checkpoint taken
+//! after them.
+//!
+//! When `squeeze_bits` returns a number of bits that is not a multiple of 8, the unused high bits
+//! of the last byte are cleared. The rest of that byte of output is discarded and the next
+//! squeeze starts at the following byte.
+//!
 //! # Credits
 //!
 //! - [`coruus/keccak-tiny`] for C implementation of keccak function
@@ -31,6 +48,7 @@
 //! [`FIPS-202`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
 //! [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 //! [`KangarooTwelve`]: https://eprint.iacr.org/2016/770.pdf
+//! [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
 //! [`coruus/keccak-tiny`]: https://github.com/coruus/keccak-tiny
 //! [`mimoo/GoKangarooTwelve`]: https://github.com/mimoo/GoKangarooTwelve
 //! [`@quininer`]: https://github.com/quininer
@@ -40,6 +58,24 @@
 
 #![no_std]
 #![deny(missing_docs)]
+// the sponge is unused when only the bare permutations are enabled
+#![cfg_attr(
+    not(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "k12",
+        feature = "turbo_shake",
+        feature = "sponge"
+    )),
+    allow(dead_code)
+)]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+extern crate alloc;
 
 use borsh::io;
 use borsh::io::Write;
@@ -55,92 +91,311 @@ const PI: [usize; 24] = [
 const WORDS: usize = 25;
 
 macro_rules! keccak_function {
-    ($doc: expr, $name: ident, $rounds: expr, $rc: expr) => {
-        #[doc = $doc]
-        #[allow(unused_assignments)]
-        #[allow(non_upper_case_globals)]
-        pub fn $name(a: &mut [u64; $crate::WORDS]) {
-            use crunchy::unroll;
+    (@rounds $a: ident, $lane: ty, $rotate: path, $rc: expr) => {{
+        #[cfg(not(feature = "small-code"))]
+        keccak_function!(@unrolled $a, $lane, $rotate, $rc);
 
-            for i in 0..$rounds {
-                let mut array: [u64; 5] = [0; 5];
+        #[cfg(feature = "small-code")]
+        keccak_function!(@looped $a, $lane, $rotate, $rc);
+    }};
+    (@unrolled $a: ident, $lane: ty, $rotate: path, $rc: expr) => {{
+        use crunchy::unroll;
 
-                // Theta
-                unroll! {
-                    for x in 0..5 {
-                        unroll! {
-                            for y_count in 0..5 {
-                                let y = y_count * 5;
-                                array[x] ^= a[x + y];
-                            }
+        for rc in $rc.iter() {
+            let mut array: [$lane; 5] = Default::default();
+
+            keccak_function!(@theta_rho_pi $a, array, $rotate);
+
+            // Chi
+            unroll! {
+                for y_step in 0..5 {
+                    let y = y_step * 5;
+
+                    unroll! {
+                        for x in 0..5 {
+                            array[x] = $a[y + x];
                         }
                     }
-                }
 
-                unroll! {
-                    for x in 0..5 {
-                        unroll! {
-                            for y_count in 0..5 {
-                                let y = y_count * 5;
-                                a[y + x] ^= array[(x + 4) % 5] ^ array[(x + 1) % 5].rotate_left(1);
-                            }
+                    unroll! {
+                        for x in 0..5 {
+                            $a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
                         }
                     }
                 }
+            };
 
-                // Rho and pi
-                let mut last = a[1];
+            // Iota
+            $a[0] ^= *rc;
+        }
+    }};
+    (@complemented $a: ident, $rc: expr) => {{
+        for rc in $rc.iter() {
+            let mut array: [u64; 5] = Default::default();
+
+            keccak_function!(@theta_rho_pi $a, array, u64::rotate_left);
+
+            // Chi, on a state with the lanes of `COMPLEMENTED_LANES` complemented
+            array.copy_from_slice(&$a[0..5]);
+            $a[0] = array[0] ^ (array[1] | array[2]);
+            $a[1] = array[1] ^ (!array[2] | array[3]);
+            $a[2] = array[2] ^ (array[3] & array[4]);
+            $a[3] = array[3] ^ (array[4] | array[0]);
+            $a[4] = array[4] ^ (array[0] & array[1]);
+
+            array.copy_from_slice(&$a[5..10]);
+            $a[5] = array[0] ^ (array[1] | array[2]);
+            $a[6] = array[1] ^ (array[2] & array[3]);
+            $a[7] = array[2] ^ (array[3] | !array[4]);
+            $a[8] = array[3] ^ (array[4] | array[0]);
+            $a[9] = array[4] ^ (array[0] & array[1]);
+
+            array.copy_from_slice(&$a[10..15]);
+            $a[10] = array[0] ^ (array[1] | array[2]);
+            $a[11] = array[1] ^ (array[2] & array[3]);
+            $a[12] = array[2] ^ (!array[3] & array[4]);
+            $a[13] = !array[3] ^ (array[4] | array[0]);
+            $a[14] = array[4] ^ (array[0] & array[1]);
+
+            array.copy_from_slice(&$a[15..20]);
+            $a[15] = array[0] ^ (array[1] & array[2]);
+            $a[16] = array[1] ^ (array[2] | array[3]);
+            $a[17] = array[2] ^ (!array[3] | array[4]);
+            $a[18] = !array[3] ^ (array[4] & array[0]);
+            $a[19] = array[4] ^ (array[0] | array[1]);
+
+            array.copy_from_slice(&$a[20..25]);
+            $a[20] = array[0] ^ (!array[1] & array[2]);
+            $a[21] = !array[1] ^ (array[2] | array[3]);
+            $a[22] = array[2] ^ (array[3] & array[4]);
+            $a[23] = array[3] ^ (array[4] | array[0]);
+            $a[24] = array[4] ^ (array[0] & array[1]);
+
+            // Iota
+            $a[0] ^= *rc;
+        }
+    }};
+    (@theta_rho_pi $a: ident, $array: ident, $rotate: path) => {{
+        use crunchy::unroll;
+
+        // Theta
+        unroll! {
+            for x in 0..5 {
                 unroll! {
-                    for x in 0..24 {
-                        array[0] = a[$crate::PI[x]];
-                        a[$crate::PI[x]] = last.rotate_left($crate::RHO[x]);
-                        last = array[0];
+                    for y_count in 0..5 {
+                        let y = y_count * 5;
+                        $array[x] ^= $a[x + y];
                     }
                 }
+            }
+        }
 
-                // Chi
+        unroll! {
+            for x in 0..5 {
                 unroll! {
-                    for y_step in 0..5 {
-                        let y = y_step * 5;
+                    for y_count in 0..5 {
+                        let y = y_count * 5;
+                        $a[y + x] ^= $array[(x + 4) % 5] ^ $rotate($array[(x + 1) % 5], 1);
+                    }
+                }
+            }
+        }
 
-                        unroll! {
-                            for x in 0..5 {
-                                array[x] = a[y + x];
-                            }
-                        }
+        // Rho and pi
+        let mut last = $a[1];
+        unroll! {
+            for x in 0..24 {
+                $array[0] = $a[$crate::PI[x]];
+                $a[$crate::PI[x]] = $rotate(last, $crate::RHO[x]);
+                last = $array[0];
+            }
+        }
+    }};
+    (@looped $a: ident, $lane: ty, $rotate: path, $rc: expr) => {{
+        for rc in $rc.iter() {
+            let mut array: [$lane; 5] = Default::default();
+
+            // Theta
+            for (x, column) in array.iter_mut().enumerate() {
+                for y in (0..$crate::WORDS).step_by(5) {
+                    *column ^= $a[x + y];
+                }
+            }
 
-                        unroll! {
-                            for x in 0..5 {
-                                a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
-                            }
-                        }
-                    }
-                };
+            for x in 0..5 {
+                let d = array[(x + 4) % 5] ^ $rotate(array[(x + 1) % 5], 1);
+                for y in (0..$crate::WORDS).step_by(5) {
+                    $a[y + x] ^= d;
+                }
+            }
 
-                // Iota
-                a[0] ^= $rc[i];
+            // Rho and pi
+            let mut last = $a[1];
+            for (pi, rho) in $crate::PI.iter().zip($crate::RHO.iter()) {
+                let next = $a[*pi];
+                $a[*pi] = $rotate(last, *rho);
+                last = next;
             }
+
+            // Chi
+            for y in (0..$crate::WORDS).step_by(5) {
+                array.copy_from_slice(&$a[y..y + 5]);
+                for x in 0..5 {
+                    $a[y + x] = array[x] ^ ((!array[(x + 1) % 5]) & (array[(x + 2) % 5]));
+                }
+            }
+
+            // Iota
+            $a[0] ^= *rc;
+        }
+    }};
+    (@lanes $a: ident, $rc: expr) => {{
+        #[cfg(not(any(target_pointer_width = "32", feature = "bit_interleaving")))]
+        keccak_function!(@rounds $a, u64, u64::rotate_left, $rc);
+
+        #[cfg(any(target_pointer_width = "32", feature = "bit_interleaving"))]
+        {
+            use $crate::interleaved::{Interleaved, RC};
+
+            // `$rc` is always the end of the round constants of `keccak-f[1600]`
+            let rc = &RC[RC.len() - $rc.len()..];
+            debug_assert!(rc.iter().zip($rc.iter()).all(|(rc, lane)| rc.to_lane() == *lane));
+            let mut lanes = Interleaved::load($a);
+            keccak_function!(@rounds lanes, Interleaved, Interleaved::rotate_left, rc);
+            Interleaved::store(&lanes, $a);
         }
+    }};
+    ($doc: expr, $name: ident, $rounds: expr, $rc: expr) => {
+        #[doc = $doc]
+        pub fn $name(a: &mut [u64; $crate::WORDS]) {
+            $crate::switch_form(a);
+            $crate::keccak_rounds(a, &$rc[..$rounds]);
+            $crate::switch_form(a);
+        }
+    };
+    ($doc: expr, $name: ident, $rc: expr) => {
+        #[doc = $doc]
+        pub fn $name(a: &mut [u64; $crate::WORDS], rounds: usize) {
+            assert!(
+                rounds <= $rc.len(),
+                "number of rounds cannot exceed {}",
+                $rc.len()
+            );
+            $crate::switch_form(a);
+            $crate::keccak_rounds(a, &$rc[$rc.len() - rounds..]);
+            $crate::switch_form(a);
+        }
+    };
+    ($doc: expr, $name: ident, $lane: ty, $rotate: path, $rounds: expr, $rc: expr) => {
+        #[doc = $doc]
+        #[allow(unused_assignments)]
+        #[allow(non_upper_case_globals)]
+        pub fn $name(a: &mut [$lane; $crate::WORDS]) {
+            keccak_function!(@rounds a, $lane, $rotate, $rc[..$rounds]);
+        }
+    };
+}
+
+/// Lanes kept complemented by the lane complementing implementation of `keccak-p[1600]`.
+const COMPLEMENTED_LANES: [usize; 6] = [1, 2, 8, 12, 17, 20];
+
+/// The default 64-bit implementation of `keccak-p[1600]` works on a state with the lanes of
+/// `COMPLEMENTED_LANES` complemented, which removes most of the NOTs of the chi step. Sponges
+/// keep their state in this form and convert it only when it's read.
+const LANE_COMPLEMENTING: bool = cfg!(not(any(
+    target_pointer_width = "32",
+    feature = "bit_interleaving",
+    feature = "small-code"
+)));
+
+/// Switches the lanes of `COMPLEMENTED_LANES` between their plain and complemented form.
+#[inline(always)]
+fn complement_lanes(a: &mut [u64; WORDS]) {
+    for &lane in COMPLEMENTED_LANES.iter() {
+        a[lane] = !a[lane];
     }
 }
 
-#[cfg(feature = "k12")]
-mod keccakp;
+/// Switches a state between its plain form and the form given by `LANE_COMPLEMENTING`.
+#[inline(always)]
+fn switch_form(a: &mut [u64; WORDS]) {
+    if LANE_COMPLEMENTING {
+        complement_lanes(a);
+    }
+}
 
-#[cfg(feature = "k12")]
-pub use keccakp::keccakp;
+/// Applies the rounds of `rc` to a state in the form given by `LANE_COMPLEMENTING`. Every
+/// `keccak-p[1600]` function shares a single, non-inlined body with the `small-code` feature.
+#[cfg_attr(feature = "small-code", inline(never))]
+#[cfg_attr(not(feature = "small-code"), inline(always))]
+#[allow(unused_assignments)]
+fn keccak_rounds(a: &mut [u64; WORDS], rc: &[u64]) {
+    if LANE_COMPLEMENTING {
+        keccak_function!(@complemented a, rc);
+    } else {
+        keccak_function!(@lanes a, rc);
+    }
+}
+
+/// Applies the rounds of `rc` to a state with the lanes of `COMPLEMENTED_LANES` complemented,
+/// the `execute_complemented` of the permutations of the crate.
+#[inline(always)]
+fn keccak_rounds_complemented(a: &mut [u64; WORDS], rc: &[u64]) {
+    if !LANE_COMPLEMENTING {
+        complement_lanes(a);
+    }
+    keccak_rounds(a, rc);
+    if !LANE_COMPLEMENTING {
+        complement_lanes(a);
+    }
+}
+
+mod error;
+
+pub use error::Error;
 
 #[cfg(any(
-    feature = "keccak",
-    feature = "shake",
-    feature = "sha3",
-    feature = "cshake",
+    feature = "k12",
     feature = "kmac",
     feature = "tuple_hash",
     feature = "parallel_hash"
 ))]
+mod checkpoint;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
+#[cfg(feature = "digest")]
+mod digest_impls;
+
+#[cfg(feature = "digest")]
+pub use digest;
+
+#[cfg(all(feature = "digest", feature = "keccak"))]
+pub use digest_impls::{Keccak224, Keccak256, Keccak384, Keccak512};
+
+#[cfg(all(feature = "digest", feature = "sha3"))]
+pub use digest_impls::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+
+#[cfg(all(feature = "digest", feature = "shake"))]
+pub use digest_impls::{Shake128, Shake256};
+
+#[cfg(all(feature = "digest", feature = "kmac"))]
+pub use digest_impls::{Kmac128, Kmac256};
+
+#[cfg(any(target_pointer_width = "32", feature = "bit_interleaving"))]
+mod interleaved;
+
+#[cfg(any(feature = "k12", feature = "turbo_shake"))]
+mod keccakp;
+
+#[cfg(any(feature = "k12", feature = "turbo_shake"))]
+pub use keccakp::{keccakp, keccakp14, keccakp_inverse, keccakp_traced};
+
 mod keccakf;
 
+pub use keccakf::{keccak_p, keccakf, KeccakPRounds};
+
 #[cfg(any(
     feature = "keccak",
     feature = "shake",
@@ -148,21 +403,49 @@ mod keccakf;
     feature = "cshake",
     feature = "kmac",
     feature = "tuple_hash",
-    feature = "parallel_hash"
+    feature = "parallel_hash",
+    feature = "sponge",
+    feature = "duplex"
 ))]
-pub use keccakf::keccakf;
+pub use keccakf::KeccakF;
+
+#[cfg(feature = "reference")]
+pub mod reference;
+
+mod steps;
+
+pub use steps::{
+    chi, chi_inverse, iota, iota_inverse, keccak_p_inverse, keccak_p_traced, keccakf_inverse,
+    keccakf_traced, pi, pi_inverse, rho, rho_inverse, theta, theta_inverse, Step,
+};
+
+mod keccakf_x4;
+
+pub use keccakf_x4::keccakf_x4;
+
+#[cfg(feature = "reduced_width")]
+mod reduced_width;
+
+#[cfg(feature = "reduced_width")]
+pub use reduced_width::{
+    keccakf100, keccakf200, keccakf25, keccakf400, keccakf50, keccakf800, KeccakF200, KeccakF400,
+    KeccakF800,
+};
 
 #[cfg(feature = "k12")]
 mod k12;
 
 #[cfg(feature = "k12")]
-pub use k12::{KangarooTwelve, KangarooTwelveXof};
+pub use k12::{
+    KangarooTwelve, KangarooTwelve256, KangarooTwelve256Xof, KangarooTwelveXof,
+    MarsupilamiFourteen, MarsupilamiFourteenXof,
+};
 
 #[cfg(feature = "keccak")]
 mod keccak;
 
 #[cfg(feature = "keccak")]
-pub use keccak::Keccak;
+pub use keccak::{keccak256_short, Keccak};
 
 #[cfg(feature = "shake")]
 mod shake;
@@ -170,11 +453,17 @@ mod shake;
 #[cfg(feature = "shake")]
 pub use shake::Shake;
 
+#[cfg(feature = "turbo_shake")]
+mod turbo_shake;
+
+#[cfg(feature = "turbo_shake")]
+pub use turbo_shake::TurboShake;
+
 #[cfg(feature = "sha3")]
 mod sha3;
 
 #[cfg(feature = "sha3")]
-pub use sha3::Sha3;
+pub use sha3::{sha3_256_short, Sha3};
 
 #[cfg(feature = "cshake")]
 mod cshake;
@@ -200,6 +489,18 @@ mod parallel_hash;
 #[cfg(feature = "parallel_hash")]
 pub use parallel_hash::{ParallelHash, ParallelHashXof};
 
+#[cfg(feature = "sponge")]
+mod sponge;
+
+#[cfg(feature = "sponge")]
+pub use sponge::{OutputMode, Sponge, SpongeBuilder};
+
+#[cfg(feature = "duplex")]
+mod duplex;
+
+#[cfg(feature = "duplex")]
+pub use duplex::Duplex;
+
 #[cfg(test)]
 use quickcheck::{Arbitrary, Gen};
 
@@ -208,7 +509,7 @@ use quickcheck::{Arbitrary, Gen};
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::Hasher;
+/// # use tari_tiny_keccak::Hasher;
 /// #
 /// # fn foo<H: Hasher>(mut hasher: H) {
 /// let input_a = b"hello world";
@@ -232,7 +533,7 @@ pub trait Hasher {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::IntoXof;
+/// # use tari_tiny_keccak::IntoXof;
 /// #
 /// # fn foo<H: IntoXof>(hasher: H) {
 /// let xof = hasher.into_xof();
@@ -259,7 +560,7 @@ pub trait IntoXof {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::Xof;
+/// # use tari_tiny_keccak::Xof;
 /// #
 /// # fn foo<X: Xof>(mut xof: X) {
 /// let mut output = [0u8; 64];
@@ -272,17 +573,91 @@ pub trait Xof {
     fn squeeze(&mut self, output: &mut [u8]);
 }
 
+/// Adapts a [`Hasher`] to `borsh::io::Write`: every write is absorbed by the hasher, so a Borsh
+/// encoding is hashed as it is serialized, without an intermediate buffer.
+///
+/// # Example
+///
+/// ```
+/// # use borsh::BorshSerialize;
+/// # use tari_tiny_keccak::{Hasher, HasherWriter};
+/// #
+/// # fn foo<H: Hasher>(mut hasher: H) {
+/// let mut output = [0u8; 32];
+/// (1u64, "name").serialize(&mut HasherWriter::new(&mut hasher)).unwrap();
+/// hasher.finalize(&mut output);
+/// # }
+/// ```
+///
+/// [`Hasher`]: trait.Hasher.html
+pub struct HasherWriter<'a, H: ?Sized> {
+    hasher: &'a mut H,
+}
+
+impl<'a, H: Hasher + ?Sized> HasherWriter<'a, H> {
+    /// Creates new [`HasherWriter`] that writes into `hasher`.
+    ///
+    /// [`HasherWriter`]: struct.HasherWriter.html
+    pub fn new(hasher: &'a mut H) -> Self {
+        HasherWriter { hasher }
+    }
+}
+
+impl<H: Hasher + ?Sized> Write for HasherWriter<'_, H> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.hasher.update(buf);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Absorbs the Borsh encoding of `value` into `hasher`, with a [`HasherWriter`]. The hasher
+/// gets the same input as from `hasher.update(&borsh::to_vec(value)?)`, without the allocation.
+///
+/// Fails only if `value` can't be serialized, e.g. if a collection is longer than `u32::MAX`.
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{hash_borsh, Hasher};
+/// #
+/// # fn foo<H: Hasher>(mut hasher: H) {
+/// let mut output = [0u8; 32];
+/// hash_borsh(&mut hasher, &[1u64, 2, 3]).unwrap();
+/// hasher.finalize(&mut output);
+/// # }
+/// ```
+///
+/// [`HasherWriter`]: struct.HasherWriter.html
+pub fn hash_borsh<H: Hasher + ?Sized, T: BorshSerialize + ?Sized>(
+    hasher: &mut H,
+    value: &T,
+) -> io::Result<()> {
+    value.serialize(&mut HasherWriter::new(hasher))
+}
+
+#[cfg(any(feature = "cshake", feature = "k12"))]
 struct EncodedLen {
     offset: usize,
     buffer: [u8; 9],
 }
 
+#[cfg(any(feature = "cshake", feature = "k12"))]
 impl EncodedLen {
     fn value(&self) -> &[u8] {
         &self.buffer[self.offset..]
     }
 }
 
+#[cfg(feature = "cshake")]
 fn left_encode(len: usize) -> EncodedLen {
     let mut buffer = [0u8; 9];
     buffer[1..].copy_from_slice(&(len as u64).to_be_bytes());
@@ -295,6 +670,7 @@ fn left_encode(len: usize) -> EncodedLen {
     }
 }
 
+#[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
 fn right_encode(len: usize) -> EncodedLen {
     let mut buffer = [0u8; 9];
     buffer[..8].copy_from_slice(&(len as u64).to_be_bytes());
@@ -303,62 +679,83 @@ fn right_encode(len: usize) -> EncodedLen {
     EncodedLen { offset, buffer }
 }
 
-/// internal buffer for hashing keccak
+/// The state of a sponge, as seen by a [`Permutation`].
+///
+/// [`Permutation`]: trait.Permutation.html
 #[derive(Default, Debug, Clone)]
 pub struct Buffer([u64; WORDS]);
 
 impl Buffer {
-    fn words(&mut self) -> &mut [u64; WORDS] {
+    /// The 25 lanes of the state, indexed by `x + 5 * y`. Bytes are absorbed into the lanes in
+    /// little-endian order.
+    pub fn words(&mut self) -> &mut [u64; WORDS] {
         &mut self.0
     }
 
-    #[cfg(target_endian = "little")]
+    /// Adds `byte` to the byte of the state at `offset`.
     #[inline]
-    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
-        let buffer: &mut [u8; WORDS * 8] = unsafe { core::mem::transmute(&mut self.0) };
-        f(&mut buffer[offset..][..len]);
+    fn xor_byte(&mut self, offset: usize, byte: u8) {
+        self.0[offset / 8] ^= u64::from(byte) << (8 * (offset % 8));
     }
 
-    #[cfg(target_endian = "big")]
+    /// The byte of the state at `offset`.
     #[inline]
-    fn execute<F: FnOnce(&mut [u8])>(&mut self, offset: usize, len: usize, f: F) {
-        fn swap_endianess(buffer: &mut [u64]) {
-            for item in buffer {
-                *item = item.swap_bytes();
-            }
-        }
-
-        let start = offset / 8;
-        let end = (offset + len + 7) / 8;
-        swap_endianess(&mut self.0[start..end]);
-        let buffer: &mut [u8; WORDS * 8] = unsafe { core::mem::transmute(&mut self.0) };
-        f(&mut buffer[offset..][..len]);
-        swap_endianess(&mut self.0[start..end]);
+    fn byte(&self, offset: usize) -> u8 {
+        (self.0[offset / 8] >> (8 * (offset % 8))) as u8
     }
 
     fn setout(&mut self, dst: &mut [u8], offset: usize, len: usize) {
-        self.execute(offset, len, |buffer| dst[..len].copy_from_slice(buffer));
+        let dst = &mut dst[..len];
+        // bytes up to the first whole lane
+        let head = core::cmp::min((8 - offset % 8) % 8, len);
+        let (head, dst) = dst.split_at_mut(head);
+        for (i, byte) in head.iter_mut().enumerate() {
+            *byte = self.byte(offset + i);
+        }
+        let offset = offset + head.len();
+        let tail = offset + (dst.len() & !7);
+        let mut chunks = dst.chunks_exact_mut(8);
+        for (i, chunk) in (&mut chunks).enumerate() {
+            chunk.copy_from_slice(&self.0[offset / 8 + i].to_le_bytes());
+        }
+        for (i, byte) in chunks.into_remainder().iter_mut().enumerate() {
+            *byte = self.byte(tail + i);
+        }
     }
 
+    #[cfg(feature = "small-code")]
+    #[inline(never)]
     fn xorin(&mut self, src: &[u8], offset: usize, len: usize) {
-        self.execute(offset, len, |dst| {
-            assert!(dst.len() <= src.len());
-            let len = dst.len();
-            let mut dst_ptr = dst.as_mut_ptr();
-            let mut src_ptr = src.as_ptr();
-            for _ in 0..len {
-                unsafe {
-                    *dst_ptr ^= *src_ptr;
-                    src_ptr = src_ptr.offset(1);
-                    dst_ptr = dst_ptr.offset(1);
-                }
-            }
-        });
+        for (i, byte) in src[..len].iter().enumerate() {
+            self.xor_byte(offset + i, *byte);
+        }
+    }
+
+    #[cfg(not(feature = "small-code"))]
+    fn xorin(&mut self, src: &[u8], offset: usize, len: usize) {
+        use core::convert::TryInto;
+
+        let src = &src[..len];
+        // bytes up to the first whole lane
+        let head = core::cmp::min((8 - offset % 8) % 8, len);
+        let (head, src) = src.split_at(head);
+        for (i, byte) in head.iter().enumerate() {
+            self.xor_byte(offset + i, *byte);
+        }
+        let offset = offset + head.len();
+        let tail = offset + (src.len() & !7);
+        let mut chunks = src.chunks_exact(8);
+        for (i, chunk) in (&mut chunks).enumerate() {
+            self.0[offset / 8 + i] ^= u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        for (i, byte) in chunks.remainder().iter().enumerate() {
+            self.xor_byte(tail + i, *byte);
+        }
     }
 
     fn pad(&mut self, offset: usize, delim: u8, rate: usize) {
-        self.execute(offset, 1, |buff| buff[0] ^= delim);
-        self.execute(rate - 1, 1, |buff| buff[0] ^= 0x80);
+        self.xor_byte(offset, delim);
+        self.xor_byte(rate - 1, 0x80);
     }
 }
 
@@ -366,8 +763,8 @@ impl Buffer {
 impl Arbitrary for Buffer {
     fn arbitrary(g: &mut Gen) -> Self {
         let mut buf = [0u64; WORDS];
-        for i in 0..WORDS {
-            buf[i] = u64::arbitrary(g);
+        for word in buf.iter_mut() {
+            *word = u64::arbitrary(g);
         }
         Buffer(buf)
     }
@@ -388,15 +785,93 @@ impl BorshDeserialize for Buffer {
         R: io::Read,
     {
         let mut buf = [0u64; WORDS];
-        for i in 0..WORDS {
-            buf[i] = BorshDeserialize::deserialize_reader(reader)?;
+        for word in buf.iter_mut() {
+            *word = BorshDeserialize::deserialize_reader(reader)?;
         }
         Ok(Self(buf))
     }
 }
 
-trait Permutation {
-    fn execute(a: &mut Buffer);
+/// The permutation that drives the sponge of a hasher.
+///
+/// Hashers built on `keccak-f[1600, 24]` are generic over the permutation and use [`KeccakF`] by
+/// default. Another implementation, for example one that offloads the permutation to dedicated
+/// hardware or one that records its inputs, can be plugged in with the `new` constructor of the
+/// hasher.
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{keccakf, Buffer, Permutation};
+/// struct MyKeccakF;
+///
+/// impl Permutation for MyKeccakF {
+///     fn execute(buffer: &mut Buffer) {
+///         keccakf(buffer.words());
+///     }
+/// }
+///
+/// # #[cfg(feature = "sha3")]
+/// # {
+/// # use tari_tiny_keccak::{Hasher, Sha3};
+/// let mut sha3 = Sha3::<MyKeccakF>::new(256);
+/// let mut output = [0u8; 32];
+/// sha3.update(b"hello world");
+/// sha3.finalize(&mut output);
+/// # }
+/// ```
+///
+/// [`KeccakF`]: struct.KeccakF.html
+pub trait Permutation {
+    /// The width `b` of the permutation in bits, 1600 by default. The rate of a [`Sponge`] or a
+    /// [`Duplex`] object built on the permutation is `b - c` bits and must be smaller than `b`.
+    ///
+    /// [`Sponge`]: struct.Sponge.html
+    /// [`Duplex`]: struct.Duplex.html
+    const WIDTH: usize = 1600;
+
+    /// Permutes the state.
+    fn execute(buffer: &mut Buffer);
+
+    /// Whether the permutation is also implemented on a state with the lanes 1, 2, 8, 12, 17 and
+    /// 20 complemented, the lane complementing transform of the Keccak implementation overview.
+    /// When it is, sponges keep their state in that form and call [`execute_complemented`]
+    /// instead of [`execute`].
+    ///
+    /// Defaults to `false`. The permutations of the crate set it only in builds that use the
+    /// lane complementing transform, which excludes 32-bit targets and the `bit_interleaving`
+    /// and `small-code` features.
+    ///
+    /// [`execute`]: #tymethod.execute
+    /// [`execute_complemented`]: #method.execute_complemented
+    const COMPLEMENTED: bool = false;
+
+    /// Permutes a state with the lanes 1, 2, 8, 12, 17 and 20 complemented. Only called when
+    /// [`COMPLEMENTED`] is `true`.
+    ///
+    /// The default complements the lanes, calls [`execute`] and complements them back.
+    ///
+    /// [`COMPLEMENTED`]: #associatedconstant.COMPLEMENTED
+    /// [`execute`]: #tymethod.execute
+    fn execute_complemented(buffer: &mut Buffer) {
+        complement_lanes(buffer.words());
+        Self::execute(buffer);
+        complement_lanes(buffer.words());
+    }
+
+    /// Permutes four independent states, as `KangarooTwelve` and `ParallelHash` do with four
+    /// chunks of their input.
+    ///
+    /// The default calls [`execute`] on every state in turn.
+    ///
+    /// [`execute`]: #tymethod.execute
+    fn execute_x4(states: &mut [[u64; WORDS]; 4]) {
+        for state in states.iter_mut() {
+            let mut buffer = Buffer(*state);
+            Self::execute(&mut buffer);
+            *state = buffer.0;
+        }
+    }
 }
 
 /// internal hashing mode for keccak
@@ -410,6 +885,31 @@ pub enum Mode {
     Squeezing = 2u8,
 }
 
+/// Length of the canonical encoding of a sponge state, as returned by the `to_bytes` method of
+/// the hashers. The encoding is made of:
+///
+/// | bytes    | content                                                                   |
+/// |----------|---------------------------------------------------------------------------|
+/// | `0..200` | the 25 lanes of the `FIPS-202` state, indexed by `x + 5 * y`, little-endian |
+/// | `200`    | the rate in bytes                                                         |
+/// | `201`    | the offset in the block of the next byte to absorb or squeeze             |
+/// | `202`    | the domain separation suffix, followed by the first bit of the padding    |
+/// | `203`    | the mode, `1` when absorbing and `2` when squeezing                       |
+///
+/// While absorbing, the padding has not been added to the state yet. The mode is `3` when
+/// squeezing after a message that is not a whole number of bytes, which can't be extended.
+///
+/// The layout is stable, so a state can be exported by one version of this crate and imported
+/// by a later one, or by another implementation that follows it.
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "turbo_shake"
+))]
+pub const STATE_BYTES: usize = WORDS * 8 + 4;
+
 #[derive(Debug)]
 struct KeccakState<P> {
     buffer: Buffer,
@@ -417,20 +917,27 @@ struct KeccakState<P> {
     rate: u8,
     delim: u8,
     mode: Mode,
+    /// Set by `update_bits` once it has padded trailing bits that don't fill a byte. Nothing can
+    /// be absorbed after them. Encoded in the mode byte, see `mode_byte`.
+    bits_finalized: bool,
     permutation: core::marker::PhantomData<P>,
 }
 
-impl<P> BorshSerialize for KeccakState<P> {
+impl<P: Permutation> BorshSerialize for KeccakState<P> {
     fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        BorshSerialize::serialize(&self.buffer, writer)?;
+        let mut buffer = self.buffer.clone();
+        if P::COMPLEMENTED {
+            complement_lanes(buffer.words());
+        }
+        BorshSerialize::serialize(&buffer, writer)?;
         BorshSerialize::serialize(&self.offset, writer)?;
         BorshSerialize::serialize(&self.rate, writer)?;
-        BorshSerialize::serialize(&self.mode, writer)?;
+        BorshSerialize::serialize(&self.mode_byte(), writer)?;
         Ok(())
     }
 }
 
-impl<P> BorshDeserialize for KeccakState<P> {
+impl<P: Permutation> BorshDeserialize for KeccakState<P> {
     fn deserialize_reader<R>(reader: &mut R) -> Result<Self, io::Error>
     where
         R: io::Read,
@@ -438,15 +945,19 @@ impl<P> BorshDeserialize for KeccakState<P> {
         let buffer = BorshDeserialize::deserialize_reader(reader)?;
         let offset = BorshDeserialize::deserialize_reader(reader)?;
         let rate = BorshDeserialize::deserialize_reader(reader)?;
-        let mode = BorshDeserialize::deserialize_reader(reader)?;
-        Ok(Self {
+        let (mode, bits_finalized) = Self::decode_mode(u8::deserialize_reader(reader)?)?;
+        let mut state = Self {
             buffer,
             offset,
             rate,
             delim: 0x01,
             mode,
+            bits_finalized,
             permutation: core::marker::PhantomData,
-        })
+        };
+        state.check()?;
+        state.complement();
+        Ok(state)
     }
 }
 
@@ -458,43 +969,195 @@ impl<P> Clone for KeccakState<P> {
             rate: self.rate,
             delim: self.delim,
             mode: self.mode,
+            bits_finalized: self.bits_finalized,
             permutation: core::marker::PhantomData,
         }
     }
 }
 
 impl<P: Permutation> KeccakState<P> {
+    /// Creates an empty state. The rate has been validated by the constructor of the hasher.
     fn new(rate: u8, delim: u8) -> Self {
-        assert!(rate != 0, "rate cannot be equal 0");
-        KeccakState {
+        debug_assert!((2..WORDS as u8 * 8).contains(&rate));
+        let mut state = KeccakState {
             buffer: Buffer::default(),
             offset: 0,
             rate,
             delim,
             mode: Mode::Absorbing,
+            bits_finalized: false,
             permutation: core::marker::PhantomData,
+        };
+        state.complement();
+        state
+    }
+    #[cfg(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "turbo_shake"
+    ))]
+    fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        let mut buffer = self.buffer.clone();
+        if P::COMPLEMENTED {
+            complement_lanes(buffer.words());
+        }
+        let mut bytes = [0u8; STATE_BYTES];
+        for (bytes, lane) in bytes.chunks_exact_mut(8).zip(buffer.words().iter()) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
         }
+        bytes[WORDS * 8] = self.rate;
+        bytes[WORDS * 8 + 1] = self.offset;
+        bytes[WORDS * 8 + 2] = self.delim;
+        bytes[WORDS * 8 + 3] = self.mode_byte();
+        bytes
     }
-    #[cfg(test)]
-    pub fn new_with(buffer: Buffer, offset: u8, rate: u8, delim: u8, mode: Mode) -> Self {
-        KeccakState {
+
+    /// Decodes a state encoded by `to_bytes`. Fails if the rate, the offset or the mode is out of
+    /// range, or if the suffix is missing its padding bit.
+    #[cfg(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "turbo_shake"
+    ))]
+    fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<Self, Error> {
+        use core::convert::TryInto;
+
+        let (mode, bits_finalized) = Self::decode_mode(bytes[WORDS * 8 + 3])?;
+        let mut buffer = Buffer::default();
+        for (lane, bytes) in buffer.words().iter_mut().zip(bytes.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let mut state = KeccakState {
             buffer,
-            offset,
-            rate,
-            delim,
+            offset: bytes[WORDS * 8 + 1],
+            rate: bytes[WORDS * 8],
+            delim: bytes[WORDS * 8 + 2],
             mode,
+            bits_finalized,
             permutation: core::marker::PhantomData,
+        };
+        state.check()?;
+        state.complement();
+        Ok(state)
+    }
+
+    /// The encoded mode: `1` when absorbing, `2` when squeezing and `3` when squeezing once
+    /// `bits_finalized` is set.
+    fn mode_byte(&self) -> u8 {
+        if self.bits_finalized {
+            3
+        } else {
+            self.mode as u8
+        }
+    }
+
+    /// Decodes the mode and `bits_finalized` from the byte written by `mode_byte`.
+    fn decode_mode(byte: u8) -> Result<(Mode, bool), Error> {
+        match byte {
+            1 => Ok((Mode::Absorbing, false)),
+            2 => Ok((Mode::Squeezing, false)),
+            3 => Ok((Mode::Squeezing, true)),
+            _ => Err(Error::InvalidState),
+        }
+    }
+
+    /// Checks that the rate, the offset and the suffix of a decoded state are in range, so that
+    /// it can't silently produce a wrong digest.
+    fn check(&self) -> Result<(), Error> {
+        let valid_rate = (2..WORDS as u8 * 8).contains(&self.rate);
+        if !valid_rate || self.offset >= self.rate || self.delim == 0 {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// Checks that a restored inner state of a hasher that has not been finalized is still
+    /// absorbing. A squeezing state would be padded twice, or not at all, when it is finalized.
+    #[cfg(any(
+        feature = "kmac",
+        feature = "tuple_hash",
+        feature = "parallel_hash",
+        feature = "k12",
+        feature = "duplex"
+    ))]
+    fn check_absorbing(&self) -> Result<(), Error> {
+        match self.mode {
+            Mode::Absorbing => Ok(()),
+            Mode::Squeezing => Err(Error::InvalidState),
+        }
+    }
+
+    /// Checks that a decoded state has the rate of one of the security levels `levels`, and a
+    /// suffix accepted by `delim`, so that it is the state of the hasher it is decoded into.
+    #[cfg(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "turbo_shake"
+    ))]
+    fn validate(&self, levels: &[u16], delim: impl Fn(u8) -> bool) -> Result<(), Error> {
+        let valid_rate = levels.iter().any(|bits| bits_to_rate(*bits) == self.rate);
+        if !valid_rate || !delim(self.delim) {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// Serializes the state like its Borsh encoding, followed by the domain separation suffix
+    /// that the encoding of `Keccak` leaves implicit.
+    #[cfg(any(
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "k12",
+        feature = "sponge"
+    ))]
+    fn serialize_with_delim<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.serialize(writer)?;
+        self.delim.serialize(writer)
+    }
+
+    /// Deserializes a state serialized by `serialize_with_delim`. The hasher checks the suffix.
+    #[cfg(any(
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake",
+        feature = "k12",
+        feature = "sponge"
+    ))]
+    fn deserialize_with_delim<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let mut state = Self::deserialize_reader(reader)?;
+        state.delim = u8::deserialize_reader(reader)?;
+        state.check()?;
+        Ok(state)
+    }
+
+    /// Switches the buffer between the form expected by `P` and the plain one.
+    fn complement(&mut self) {
+        if P::COMPLEMENTED {
+            complement_lanes(self.buffer.words());
         }
     }
 
     fn keccak(&mut self) {
-        P::execute(&mut self.buffer);
+        if P::COMPLEMENTED {
+            P::execute_complemented(&mut self.buffer);
+        } else {
+            P::execute(&mut self.buffer);
+        }
     }
 
     fn update(&mut self, input: &[u8]) {
-        if self.rate >= 200 || self.rate <= 1 || self.rate <= self.offset {
-            return;
-        }
+        debug_assert!(self.offset < self.rate);
+        assert!(
+            !self.bits_finalized,
+            "cannot absorb after a message that is not a whole number of bytes"
+        );
         if let Mode::Squeezing = self.mode {
             self.mode = Mode::Absorbing;
             self.fill_block();
@@ -504,7 +1167,7 @@ impl<P: Permutation> KeccakState<P> {
         let mut l = input.len();
         let mut rate = (self.rate - self.offset) as usize;
         let mut offset = self.offset as usize;
-        while l >= rate as usize {
+        while l >= rate {
             self.buffer.xorin(&input[ip..], offset, rate);
             self.keccak();
             ip += rate;
@@ -518,15 +1181,80 @@ impl<P: Permutation> KeccakState<P> {
         self.offset = (offset + l) as u8;
     }
 
+    /// Absorbs the first `bit_len` bits of `input`, see the bit-oriented messages section of the
+    /// crate docs. Trailing bits that don't make a whole byte are padded right away and set
+    /// `bits_finalized`.
+    #[cfg(any(
+        feature = "keccak",
+        feature = "shake",
+        feature = "sha3",
+        feature = "cshake"
+    ))]
+    fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        assert!(
+            bit_len <= input.len() * 8,
+            "input is shorter than {} bits",
+            bit_len
+        );
+        let len = bit_len / 8;
+        self.update(&input[..len]);
+        let trailing = bit_len % 8;
+        if trailing != 0 {
+            let bits = u16::from(input[len]) & ((1 << trailing) - 1);
+            self.mode = Mode::Squeezing;
+            self.pad_bits(bits | u16::from(self.delim) << trailing);
+            self.fill_block();
+            self.bits_finalized = true;
+        }
+    }
+
+    /// Fills `output` with the next `bit_len` bits of the output, see the bit-oriented messages
+    /// section of the crate docs.
+    #[cfg(any(feature = "shake", feature = "cshake"))]
+    fn squeeze_bits(&mut self, output: &mut [u8], bit_len: usize) {
+        let len = bit_len.div_ceil(8);
+        assert!(
+            len <= output.len(),
+            "output is shorter than {} bits",
+            bit_len
+        );
+        self.squeeze(&mut output[..len]);
+        let trailing = bit_len % 8;
+        if trailing != 0 {
+            output[len - 1] &= (1 << trailing) - 1;
+        }
+    }
+
     fn pad(&mut self) {
-        self.buffer
-            .pad(self.offset as usize, self.delim, self.rate as usize);
+        self.pad_bits(u16::from(self.delim));
     }
 
-    fn squeeze(&mut self, output: &mut [u8]) {
-        if self.rate >= 200 || self.rate <= 1 || self.rate <= self.offset {
-            return;
+    /// Appends `delimited` and the padding to the absorbed input. `delimited` holds the last
+    /// bits of the message, followed by the suffix and by the first bit of the padding.
+    fn pad_bits(&mut self, mut delimited: u16) {
+        let rate = self.rate as usize;
+        let mut offset = self.offset as usize;
+        if delimited > 0xff {
+            self.buffer.xorin(&[delimited as u8], offset, 1);
+            delimited >>= 8;
+            offset += 1;
+            if offset == rate {
+                self.keccak();
+                offset = 0;
+            }
         }
+        if delimited & 0x80 != 0 && offset == rate - 1 {
+            // the last bit of the padding doesn't fit in this block anymore
+            self.buffer.xorin(&[delimited as u8], offset, 1);
+            self.keccak();
+            self.buffer.xorin(&[0x80], rate - 1, 1);
+        } else {
+            self.buffer.pad(offset, delimited as u8, rate);
+        }
+    }
+
+    fn squeeze(&mut self, output: &mut [u8]) {
+        debug_assert!(self.offset < self.rate);
         if let Mode::Absorbing = self.mode {
             self.mode = Mode::Squeezing;
             self.pad();
@@ -539,7 +1267,7 @@ impl<P: Permutation> KeccakState<P> {
         let mut rate = (self.rate - self.offset) as usize;
         let mut offset = self.offset as usize;
         while l >= rate {
-            self.buffer.setout(&mut output[op..], offset, rate);
+            self.setout(&mut output[op..], offset, rate);
             self.keccak();
             op += rate;
             l -= rate;
@@ -547,10 +1275,16 @@ impl<P: Permutation> KeccakState<P> {
             offset = 0;
         }
 
-        self.buffer.setout(&mut output[op..], offset, l);
+        self.setout(&mut output[op..], offset, l);
         self.offset = (offset + l) as u8;
     }
 
+    fn setout(&mut self, dst: &mut [u8], offset: usize, len: usize) {
+        self.complement();
+        self.buffer.setout(dst, offset, len);
+        self.complement();
+    }
+
     fn finalize(mut self, output: &mut [u8]) {
         self.squeeze(output);
     }
@@ -560,22 +1294,124 @@ impl<P: Permutation> KeccakState<P> {
         self.offset = 0;
     }
 
+    /// Pads the absorbed input with zeros to a whole number of blocks, like `bytepad` of
+    /// SP800-185. Does nothing if the input already fills its last block.
+    #[cfg(feature = "cshake")]
+    fn bytepad(&mut self) {
+        if self.offset != 0 {
+            self.fill_block();
+        }
+    }
+
+    #[cfg(feature = "k12")]
     fn reset(&mut self) {
         self.buffer = Buffer::default();
+        self.complement();
         self.offset = 0;
         self.mode = Mode::Absorbing;
     }
 }
 
+/// Checks at compile time that a message of `N` bytes fits in one block of `RATE` bytes, with
+/// room left for the padding.
+#[cfg(any(feature = "keccak", feature = "sha3"))]
+struct ShortInput<const N: usize, const RATE: usize>;
+
+#[cfg(any(feature = "keccak", feature = "sha3"))]
+impl<const N: usize, const RATE: usize> ShortInput<N, RATE> {
+    const FITS: () = assert!(N < RATE, "input must be shorter than the rate");
+}
+
+/// Hashes a message that fits in one block with a single permutation. The padded block is built
+/// directly in the state, without the bookkeeping of `KeccakState`.
+#[cfg(any(feature = "keccak", feature = "sha3"))]
+fn hash_short<const N: usize, const RATE: usize, const LEN: usize>(
+    input: &[u8; N],
+    delim: u8,
+) -> [u8; LEN] {
+    #[allow(clippy::let_unit_value)]
+    let () = ShortInput::<N, RATE>::FITS;
+    let mut buffer = Buffer::default();
+    buffer.xorin(input, 0, N);
+    buffer.pad(N, delim, RATE);
+    let words = buffer.words();
+    switch_form(words);
+    keccak_rounds(words, &keccakf::RC);
+    switch_form(words);
+    let mut output = [0u8; LEN];
+    buffer.setout(&mut output, 0, LEN);
+    output
+}
+
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "k12",
+    feature = "turbo_shake"
+))]
 fn bits_to_rate(bits: u16) -> u8 {
     //max size is 512 -> (200-512/4)<255
     (200u16.saturating_sub(bits / 4)) as u8
 }
 
+/// Rate in bytes of a function with a security level of `bits` bits, which must be one of
+/// `levels`.
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake"
+))]
+fn security_level_to_rate(bits: u16, levels: &[u16]) -> Result<u8, Error> {
+    if levels.contains(&bits) {
+        Ok(bits_to_rate(bits))
+    } else {
+        Err(Error::InvalidSecurityLevel(bits))
+    }
+}
+
+/// Rate in bytes of a sponge with a capacity of `capacity` bits on a permutation of `width` bits.
+/// The rate must be a whole number of bytes, of at least 2 bytes and smaller than the width.
+#[cfg(any(feature = "sponge", feature = "duplex"))]
+fn capacity_to_rate(capacity: usize, width: usize) -> Result<u8, Error> {
+    let rate = width.saturating_sub(capacity);
+    if rate & 7 != 0 {
+        return Err(Error::FractionalRate);
+    }
+    if capacity == 0 || !(16..WORDS * 64).contains(&rate) {
+        return Err(Error::RateOutOfRange);
+    }
+    Ok((rate / 8) as u8)
+}
+
+/// Unwraps the result of a `try_*` constructor, panicking with the message of its error.
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "turbo_shake",
+    feature = "sponge",
+    feature = "duplex"
+))]
+#[track_caller]
+fn or_panic<T>(result: Result<T, Error>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(err) => panic!("{}", err),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{left_encode, right_encode};
+    #[cfg(feature = "cshake")]
+    use crate::left_encode;
+    #[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
+    use crate::right_encode;
 
+    #[cfg(feature = "cshake")]
     #[test]
     fn test_left_encode() {
         assert_eq!(left_encode(0).value(), &[1, 0]);
@@ -585,6 +1421,7 @@ mod tests {
         assert_eq!(left_encode(54321).value(), &[2, 212, 49]);
     }
 
+    #[cfg(any(feature = "kmac", feature = "tuple_hash", feature = "parallel_hash"))]
     #[test]
     fn test_right_encode() {
         assert_eq!(right_encode(0).value(), &[0, 1]);
@@ -594,8 +1431,12 @@ mod tests {
         assert_eq!(right_encode(54321).value(), &[212, 49, 2]);
     }
 
+    #[cfg(feature = "keccak")]
     mod quicktest {
+        extern crate std;
+
         use crate::{Buffer, Hasher, Keccak, Mode};
+        use borsh::BorshDeserialize;
         use quickcheck::{quickcheck, Arbitrary, Gen};
 
         #[derive(Clone, Debug)]
@@ -610,33 +1451,622 @@ mod tests {
         impl Arbitrary for Data {
             fn arbitrary(g: &mut Gen) -> Data {
                 let mut buf = [0u8; 25];
-                for i in 0..25 {
-                    buf[i] = u8::arbitrary(g);
+                for byte in buf.iter_mut() {
+                    *byte = u8::arbitrary(g);
                 }
                 Data(buf)
             }
         }
         #[test]
         fn test_ser_deserialize() {
+            fn test_hashing(buffer: Buffer, offset: u8, rate: u8, mode: Mode, hash: Data) -> bool {
+                let mut bytes = borsh::to_vec(&buffer).unwrap();
+                bytes.extend_from_slice(&[offset, rate, mode as u8]);
+                let valid = [144, 136, 104, 72].contains(&rate) && offset < rate;
+                match Keccak::try_from_slice(&bytes) {
+                    Ok(mut keccak) => {
+                        keccak.update(hash.as_slice());
+                        let mut out: [u8; 32] = [0; 32];
+                        keccak.finalize(&mut out);
+                        valid
+                    }
+                    Err(_) => !valid,
+                }
+            }
+
             fn test_hashing_a(buffer: Buffer, offset: u8, rate: u8, hash: Data) -> bool {
-                let mode = Mode::Absorbing;
-                let mut keccak = Keccak::new_with(buffer, offset, rate, mode);
-                keccak.update(hash.as_slice());
-                let mut out: [u8; 32] = [0; 32];
-                keccak.finalize(&mut out);
-                true
+                test_hashing(buffer, offset, rate, Mode::Absorbing, hash)
             }
 
             fn test_hashing_s(buffer: Buffer, offset: u8, rate: u8, hash: Data) -> bool {
-                let mode = Mode::Squeezing;
-                let mut keccak = Keccak::new_with(buffer, offset, rate, mode);
-                keccak.update(hash.as_slice());
-                let mut out: [u8; 32] = [0; 32];
-                keccak.finalize(&mut out);
-                true
+                test_hashing(buffer, offset, rate, Mode::Squeezing, hash)
             }
             quickcheck(test_hashing_a as fn(Buffer, u8, u8, Data) -> bool);
             quickcheck(test_hashing_s as fn(Buffer, u8, u8, Data) -> bool);
         }
     }
+
+    mod keccak_p {
+        use crate::{keccak_p, keccakf, Buffer, KeccakPRounds, KeccakState};
+        use quickcheck::quickcheck;
+
+        #[test]
+        fn test_keccak_p_24_rounds_is_keccakf() {
+            fn prop(buffer: Buffer) -> bool {
+                let mut expected = buffer.0;
+                let mut state = buffer.0;
+                keccakf(&mut expected);
+                keccak_p(&mut state, 24);
+                expected == state
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        #[cfg(any(feature = "k12", feature = "turbo_shake"))]
+        #[test]
+        fn test_keccak_p_reduced_rounds() {
+            use crate::{keccakp, keccakp14};
+
+            fn prop(buffer: Buffer) -> bool {
+                let mut expected12 = buffer.0;
+                let mut expected14 = buffer.0;
+                let mut state12 = buffer.0;
+                let mut state14 = buffer.0;
+                keccakp(&mut expected12);
+                keccakp14(&mut expected14);
+                keccak_p(&mut state12, 12);
+                keccak_p(&mut state14, 14);
+                expected12 == state12 && expected14 == state14
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        #[test]
+        fn test_keccak_p_x4() {
+            use crate::keccakf_x4::keccak_p_x4;
+
+            fn prop(a: Buffer, b: Buffer, c: Buffer, d: Buffer, rounds: u8) -> bool {
+                let rounds = rounds as usize % 25;
+                let mut expected = [a.0, b.0, c.0, d.0];
+                let mut states = expected;
+                for state in expected.iter_mut() {
+                    keccak_p(state, rounds);
+                }
+                keccak_p_x4(&mut states, rounds);
+                expected == states
+            }
+            quickcheck(prop as fn(Buffer, Buffer, Buffer, Buffer, u8) -> bool);
+        }
+
+        #[test]
+        fn test_keccakf_zero_state() {
+            let mut state = [0u64; 25];
+            keccakf(&mut state);
+            assert_eq!(state[0], 0xf1258f7940e1dde7);
+            assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+        }
+
+        #[test]
+        fn test_keccak_p_zero_rounds() {
+            let mut state = [0x0123_4567_89ab_cdefu64; 25];
+            keccak_p(&mut state, 0);
+            assert_eq!(state, [0x0123_4567_89ab_cdefu64; 25]);
+        }
+
+        #[test]
+        #[should_panic]
+        fn test_keccak_p_too_many_rounds() {
+            keccak_p(&mut [0u64; 25], 25);
+        }
+
+        #[test]
+        fn test_keccak_p_rounds_sponge() {
+            // SHA3-256 of the empty string
+            let expected = b"\
+                \xa7\xff\xc6\xf8\xbf\x1e\xd7\x66\x51\xc1\x47\x56\xa0\x61\xd6\x62\
+                \xf5\x80\xff\x4d\xe4\x3b\x49\xfa\x82\xd8\x0a\x4b\x80\xf8\x43\x4a\
+            ";
+            let state: KeccakState<KeccakPRounds<24>> = KeccakState::new(136, 0x06);
+            let mut output = [0u8; 32];
+            state.finalize(&mut output);
+            assert_eq!(expected, &output);
+        }
+    }
+
+    mod steps {
+        use crate::*;
+        use quickcheck::quickcheck;
+
+        fn inverts(step: fn(&mut [u64; 25]), inverse: fn(&mut [u64; 25]), buffer: &Buffer) -> bool {
+            let mut state = buffer.0;
+            step(&mut state);
+            inverse(&mut state);
+            state == buffer.0
+        }
+
+        #[test]
+        fn test_step_inverses() {
+            fn prop(buffer: Buffer) -> bool {
+                inverts(theta, theta_inverse, &buffer)
+                    && inverts(rho, rho_inverse, &buffer)
+                    && inverts(pi, pi_inverse, &buffer)
+                    && inverts(chi, chi_inverse, &buffer)
+                    && inverts(|a| iota(a, 3), |a| iota_inverse(a, 3), &buffer)
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        #[test]
+        fn test_steps_compose_keccakf() {
+            fn prop(buffer: Buffer) -> bool {
+                let mut expected = buffer.0;
+                let mut state = buffer.0;
+                keccakf(&mut expected);
+                for round in 0..24 {
+                    theta(&mut state);
+                    rho(&mut state);
+                    pi(&mut state);
+                    chi(&mut state);
+                    iota(&mut state, round);
+                }
+                expected == state
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        #[test]
+        fn test_keccakf_inverse() {
+            fn prop(buffer: Buffer) -> bool {
+                inverts(keccakf, keccakf_inverse, &buffer)
+                    && inverts(keccakf_inverse, keccakf, &buffer)
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        #[test]
+        fn test_keccak_p_inverse() {
+            fn prop(buffer: Buffer, rounds: u8) -> bool {
+                let rounds = rounds as usize % 25;
+                let mut state = buffer.0;
+                keccak_p(&mut state, rounds);
+                keccak_p_inverse(&mut state, rounds);
+                state == buffer.0
+            }
+            quickcheck(prop as fn(Buffer, u8) -> bool);
+        }
+
+        #[test]
+        fn test_keccakf_traced() {
+            fn prop(buffer: Buffer) -> bool {
+                let mut expected = buffer.0;
+                let mut state = buffer.0;
+                let mut traced = buffer.0;
+                let mut steps = 0;
+                let mut consistent = true;
+                keccakf(&mut expected);
+                keccakf_traced(&mut state, |round, step, a| {
+                    match step {
+                        Step::Theta => theta(&mut traced),
+                        Step::Rho => rho(&mut traced),
+                        Step::Pi => pi(&mut traced),
+                        Step::Chi => chi(&mut traced),
+                        Step::Iota => iota(&mut traced, round),
+                    }
+                    consistent &= step_index(step) == steps % 5 && round == steps / 5;
+                    consistent &= traced == *a;
+                    steps += 1;
+                });
+                consistent && steps == 24 * 5 && expected == state
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+
+        fn step_index(step: Step) -> usize {
+            [Step::Theta, Step::Rho, Step::Pi, Step::Chi, Step::Iota]
+                .iter()
+                .position(|s| *s == step)
+                .unwrap()
+        }
+
+        #[test]
+        fn test_keccakf_traced_zero_state() {
+            // first round of KeccakF-1600-IntermediateValues.txt
+            let mut state = [0u64; 25];
+            keccakf_traced(&mut state, |round, step, a| {
+                if round == 0 && step == Step::Iota {
+                    assert_eq!(a[0], 1);
+                    assert!(a[1..].iter().all(|lane| *lane == 0));
+                }
+            });
+        }
+
+        #[test]
+        fn test_keccakf_traced_intermediate_values() {
+            // KeccakF-1600-IntermediateValues.txt of the Keccak team permutes the all-zero state
+            // twice. These are the lanes `A[x + 5y]` of the input of the second permutation and
+            // after every step of its first round.
+            const INPUT: [u64; 25] = [
+                0xf1258f7940e1dde7,
+                0x84d5ccf933c0478a,
+                0xd598261ea65aa9ee,
+                0xbd1547306f80494d,
+                0x8b284e056253d057,
+                0xff97a42d7f8e6fd4,
+                0x90fee5a0a44647c4,
+                0x8c5bda0cd6192e76,
+                0xad30a6f71b19059c,
+                0x30935ab7d08ffc64,
+                0xeb5aa93f2317d635,
+                0xa9a6e6260d712103,
+                0x81a57c16dbcf555f,
+                0x43b831cd0347c826,
+                0x01f22f1a11a5569f,
+                0x05e5635a21d9ae61,
+                0x64befef28cc970f2,
+                0x613670957bc46611,
+                0xb87c5a554fd00ecb,
+                0x8c3ee88a1ccf32c8,
+                0x940c7922ae3a2614,
+                0x1841f924a2c509e4,
+                0x16f53526e70465c2,
+                0x75f644e97f30a13b,
+                0xeaf1ff7b5ceca249,
+            ];
+            const ROUND_0: [[u64; 25]; 5] = [
+                [
+                    0xaf463273ca4d877d,
+                    0xaf9fdf84cec209d0,
+                    0x28c573db9cdda7ba,
+                    0xabbcda349e794c02,
+                    0xfd3cb094025a23b6,
+                    0xa1f41927f522354e,
+                    0xbbb4f6dd5944099e,
+                    0x71068fc9ec9e2022,
+                    0xbb993bf3eae000d3,
+                    0x4687a426b0860f85,
+                    0xb5391435a9bb8caf,
+                    0x82ecf55bf0736f59,
+                    0x7cf829d3e1485b0b,
+                    0x5511acc9f2becd69,
+                    0x77e6d18b71aca57e,
+                    0x5b86de50ab75f4fb,
+                    0x4ff4ed8f71cb3ea8,
+                    0x9c6b255041436845,
+                    0xaed5c751be290b84,
+                    0xfa2a161b7cc6c129,
+                    0xca6fc42824967c8e,
+                    0x330bea595fc747be,
+                    0xeba860e3dd836b96,
+                    0x635fd9ed8ec9a474,
+                    0x9ce501ea3ce551a8,
+                ],
+                [
+                    0xaf463273ca4d877d,
+                    0x5f3fbf099d8413a1,
+                    0x8a315cf6e73769ee,
+                    0x49e794c02abbcda3,
+                    0xa012d11db7e9e584,
+                    0x522354ea1f41927f,
+                    0x4099ebbb4f6dd594,
+                    0x41a3f27b2788089c,
+                    0x69ddcc9df9f57000,
+                    0x426b0860f854687a,
+                    0xa9c8a1ad4ddc657d,
+                    0xb3d56fc1cdbd660b,
+                    0x42d85be7c14e9f0a,
+                    0x93e57d9ad2aa2359,
+                    0xd652bf3bf368c5b8,
+                    0xebe9f6b70dbca156,
+                    0x67d509fe9db1ee39,
+                    0x92a820a1b422ce35,
+                    0xea37c5217095dab8,
+                    0x2a161b7cc6c129fa,
+                    0x10a09259f23b29bf,
+                    0xcc2fa9657f1d1ef8,
+                    0xdd750c1c7bb06d72,
+                    0x74635fd9ed8ec9a4,
+                    0x407a8f39546a2739,
+                ],
+                [
+                    0xaf463273ca4d877d,
+                    0x4099ebbb4f6dd594,
+                    0x42d85be7c14e9f0a,
+                    0xea37c5217095dab8,
+                    0x407a8f39546a2739,
+                    0x49e794c02abbcda3,
+                    0x426b0860f854687a,
+                    0xa9c8a1ad4ddc657d,
+                    0x67d509fe9db1ee39,
+                    0xdd750c1c7bb06d72,
+                    0x5f3fbf099d8413a1,
+                    0x41a3f27b2788089c,
+                    0x93e57d9ad2aa2359,
+                    0x2a161b7cc6c129fa,
+                    0x10a09259f23b29bf,
+                    0xa012d11db7e9e584,
+                    0x522354ea1f41927f,
+                    0xb3d56fc1cdbd660b,
+                    0x92a820a1b422ce35,
+                    0x74635fd9ed8ec9a4,
+                    0x8a315cf6e73769ee,
+                    0x69ddcc9df9f57000,
+                    0xd652bf3bf368c5b8,
+                    0xebe9f6b70dbca156,
+                    0xcc2fa9657f1d1ef8,
+                ],
+                [
+                    0xad0622374a4f8d77,
+                    0xe8be6fbb7ffc9524,
+                    0x429051ffc524ba0b,
+                    0x4533f563fa905afc,
+                    0x00e346b1514a77b9,
+                    0xe067354d2f33c8a6,
+                    0x047e00326875e27a,
+                    0x31e8a5ad2fdc643f,
+                    0x6757993e9dba6eb8,
+                    0xdf7d043cabf44d2a,
+                    0xcd7bb2894da630e0,
+                    0x69b1f01f23c9003e,
+                    0x8345fd9be290235c,
+                    0x6509367ccb453bfa,
+                    0x1020d22bd03321a3,
+                    0x01c6fa1c77558184,
+                    0x520b54ca2f431a4b,
+                    0xd79630998431678b,
+                    0x12b8a0a5a643ea35,
+                    0x26425b3be58edbdf,
+                    0x1c336fd4e53fec56,
+                    0x40748c19f5615046,
+                    0xd254b67b8169db10,
+                    0xe9f9a2258d9ec050,
+                    0xade3296c67dd0ef8,
+                ],
+                [
+                    0xad0622374a4f8d76,
+                    0xe8be6fbb7ffc9524,
+                    0x429051ffc524ba0b,
+                    0x4533f563fa905afc,
+                    0x00e346b1514a77b9,
+                    0xe067354d2f33c8a6,
+                    0x047e00326875e27a,
+                    0x31e8a5ad2fdc643f,
+                    0x6757993e9dba6eb8,
+                    0xdf7d043cabf44d2a,
+                    0xcd7bb2894da630e0,
+                    0x69b1f01f23c9003e,
+                    0x8345fd9be290235c,
+                    0x6509367ccb453bfa,
+                    0x1020d22bd03321a3,
+                    0x01c6fa1c77558184,
+                    0x520b54ca2f431a4b,
+                    0xd79630998431678b,
+                    0x12b8a0a5a643ea35,
+                    0x26425b3be58edbdf,
+                    0x1c336fd4e53fec56,
+                    0x40748c19f5615046,
+                    0xd254b67b8169db10,
+                    0xe9f9a2258d9ec050,
+                    0xade3296c67dd0ef8,
+                ],
+            ];
+
+            let mut state = [0u64; 25];
+            keccakf(&mut state);
+            assert_eq!(INPUT, state);
+            keccakf_traced(&mut state, |round, step, a| {
+                if round == 0 {
+                    assert_eq!(&ROUND_0[step_index(step)], a, "{:?}", step);
+                }
+            });
+        }
+
+        #[cfg(any(feature = "k12", feature = "turbo_shake"))]
+        #[test]
+        fn test_keccakp_traced() {
+            let mut expected = [0x0123_4567_89ab_cdefu64; 25];
+            let mut state = expected;
+            let mut rounds = [0usize; 24];
+            keccakp(&mut expected);
+            keccakp_traced(&mut state, |round, _, _| rounds[round] += 1);
+            assert_eq!(expected, state);
+            assert_eq!(&[0; 12], &rounds[..12]);
+            assert_eq!(&[5; 12], &rounds[12..]);
+        }
+
+        #[cfg(any(feature = "k12", feature = "turbo_shake"))]
+        #[test]
+        fn test_keccakp_inverse() {
+            fn prop(buffer: Buffer) -> bool {
+                inverts(keccakp, keccakp_inverse, &buffer)
+            }
+            quickcheck(prop as fn(Buffer) -> bool);
+        }
+    }
+
+    #[cfg(feature = "reference")]
+    mod reference {
+        use crate::reference::{bytes_to_bits, pad10_1};
+        use crate::Buffer;
+        use alloc::vec::Vec;
+        use quickcheck::quickcheck;
+
+        fn bits(buffer: &Buffer) -> Vec<bool> {
+            let bytes: Vec<u8> = buffer
+                .0
+                .iter()
+                .flat_map(|lane| lane.to_le_bytes())
+                .collect();
+            bytes_to_bits(&bytes)
+        }
+
+        #[test]
+        fn test_xorin() {
+            fn prop(mut buffer: Buffer, data: Vec<u8>, offset: u8) -> bool {
+                let offset = offset as usize % 200;
+                let len = core::cmp::min(data.len(), 200 - offset);
+                let mut expected = bits(&buffer);
+                for (bit, data) in expected[8 * offset..]
+                    .iter_mut()
+                    .zip(bytes_to_bits(&data[..len]))
+                {
+                    *bit ^= data;
+                }
+                buffer.xorin(&data, offset, len);
+                bits(&buffer) == expected
+            }
+            quickcheck(prop as fn(Buffer, Vec<u8>, u8) -> bool);
+        }
+
+        #[test]
+        fn test_setout() {
+            fn prop(mut buffer: Buffer, offset: u8, len: u8) -> bool {
+                let offset = offset as usize % 200;
+                let len = len as usize % (201 - offset);
+                let mut output = [0u8; 200];
+                buffer.setout(&mut output, offset, len);
+                bytes_to_bits(&output[..len]) == bits(&buffer)[8 * offset..8 * (offset + len)]
+            }
+            quickcheck(prop as fn(Buffer, u8, u8) -> bool);
+        }
+
+        #[test]
+        fn test_pad() {
+            fn prop(mut buffer: Buffer, rate: u8, offset: u8, suffix: u8, suffix_len: u8) -> bool {
+                let rate = 1 + rate as usize % 199;
+                let offset = offset as usize % rate;
+                let suffix_len = suffix_len as usize % 7;
+                let suffix = suffix & ((1 << suffix_len) - 1);
+                let delim = suffix | 1 << suffix_len;
+
+                let mut padding = Vec::new();
+                padding.resize(8 * offset, false);
+                padding.extend((0..suffix_len).map(|i| suffix >> i & 1 == 1));
+                padding.extend(pad10_1(8 * rate, padding.len()));
+                let mut expected = bits(&buffer);
+                for (bit, padding) in expected.iter_mut().zip(padding) {
+                    *bit ^= padding;
+                }
+
+                buffer.pad(offset, delim, rate);
+                bits(&buffer) == expected
+            }
+            quickcheck(prop as fn(Buffer, u8, u8, u8, u8) -> bool);
+        }
+    }
+
+    mod lane_complementing {
+        extern crate std;
+
+        use crate::{keccakf, Buffer, KeccakPRounds, KeccakState};
+        use borsh::{BorshDeserialize, BorshSerialize};
+        use quickcheck::quickcheck;
+
+        type State = KeccakState<KeccakPRounds<24>>;
+
+        #[test]
+        fn test_serialized_state_is_not_complemented() {
+            let state = State::new(136, 0x06);
+            let bytes = borsh::to_vec(&state).unwrap();
+            assert!(bytes[..200].iter().all(|byte| *byte == 0));
+
+            let mut state = State::new(200 - 64, 0x06);
+            state.update(&[0xff; 136]);
+            let mut expected = [0u64; 25];
+            expected[..17].copy_from_slice(&[u64::MAX; 17]);
+            keccakf(&mut expected);
+            let bytes = borsh::to_vec(&state).unwrap();
+            let buffer = Buffer::try_from_slice(&bytes[..200]).unwrap();
+            assert_eq!(expected, buffer.0);
+        }
+
+        #[test]
+        fn test_deserialized_state_continues() {
+            fn prop(data: std::vec::Vec<u8>, split: usize) -> bool {
+                let split = split % (data.len() + 1);
+                let mut state = State::new(136, 0x06);
+                state.update(&data[..split]);
+                let mut bytes = std::vec::Vec::new();
+                state.serialize(&mut bytes).unwrap();
+                let mut restored = State::try_from_slice(&bytes).unwrap();
+                restored.delim = 0x06;
+                state.update(&data[split..]);
+                restored.update(&data[split..]);
+                let mut expected = [0u8; 32];
+                let mut output = [0u8; 32];
+                state.finalize(&mut expected);
+                restored.finalize(&mut output);
+                expected == output
+            }
+            quickcheck(prop as fn(std::vec::Vec<u8>, usize) -> bool);
+        }
+    }
+
+    #[cfg(any(target_pointer_width = "32", feature = "bit_interleaving"))]
+    mod interleaved {
+        use crate::interleaved::Interleaved;
+        use quickcheck::quickcheck;
+
+        #[test]
+        fn test_interleaved_lane_roundtrip() {
+            fn prop(lane: u64) -> bool {
+                Interleaved::from_lane(lane).to_lane() == lane
+            }
+            quickcheck(prop as fn(u64) -> bool);
+        }
+
+        #[test]
+        fn test_interleaved_rotate_left() {
+            fn prop(lane: u64, n: u8) -> bool {
+                let n = u32::from(n) % 64;
+                Interleaved::from_lane(lane).rotate_left(n).to_lane() == lane.rotate_left(n)
+            }
+            quickcheck(prop as fn(u64, u8) -> bool);
+        }
+    }
+
+    #[cfg(feature = "reduced_width")]
+    mod reduced_width {
+        use crate::{KeccakF200, KeccakF400, KeccakF800, KeccakState, Permutation};
+
+        fn sponge<P: Permutation>(rate: u8, expected: &[u8]) {
+            let mut state: KeccakState<P> = KeccakState::new(rate, 0x01);
+            for _ in 0..30 {
+                state.update(b"abc");
+            }
+            let mut output = [0u8; 40];
+            state.squeeze(&mut output[..7]);
+            state.squeeze(&mut output[7..]);
+            assert_eq!(expected, &output[..]);
+        }
+
+        #[test]
+        fn test_keccakf200_sponge() {
+            let expected = b"\
+                \xfe\x1d\x76\xb5\x6b\x6c\xe8\x61\xc4\xde\xd9\x1f\x36\x11\x90\x46\
+                \x7c\x77\x1e\x02\xd5\x6a\xdf\x68\x76\x43\xd5\x85\x8f\xcf\x98\x82\
+                \xb2\xb0\xa9\xa4\x8a\xb6\x3e\xaf\
+            ";
+            sponge::<KeccakF200>(18, expected);
+        }
+
+        #[test]
+        fn test_keccakf400_sponge() {
+            let expected = b"\
+                \xc8\x6f\x43\x59\xf3\x0c\xd3\x5a\xed\x53\xc6\x8a\x42\x8a\xb3\xc7\
+                \xa3\x4a\xea\x7f\x78\x61\xf5\x40\x29\x0c\x81\x74\x0c\x89\x4a\xfa\
+                \x55\xee\x84\x27\x36\x97\xea\x56\
+            ";
+            sponge::<KeccakF400>(34, expected);
+        }
+
+        #[test]
+        fn test_keccakf800_sponge() {
+            let expected = b"\
+                \x3a\xa5\xf5\x5e\x7b\xbc\xf7\x84\xa8\xe4\x7b\x1e\x00\xc9\x53\x7a\
+                \xf7\xcb\x5c\x48\x2f\x13\x81\x29\x8a\xd4\x34\x47\x87\x6a\xde\x94\
+                \xf3\xd8\x28\xe0\x38\xcc\xf0\x5c\
+            ";
+            sponge::<KeccakF800>(68, expected);
+        }
+    }
 }
@@ -1,8 +1,15 @@
-use crate::{left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::{
+    bits_to_rate,
+    checkpoint::{read_header, read_len, write_header, Algorithm},
+    keccakf::KeccakF,
+    keccakf_x4::hash_x4,
+    left_encode, or_panic, right_encode, CShake, Error, Hasher, IntoXof, Permutation, Xof,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 #[derive(Clone)]
-struct UnfinishedState {
-    state: CShake,
+struct UnfinishedState<P> {
+    state: CShake<P>,
     absorbed: usize,
 }
 
@@ -48,17 +55,77 @@ impl Suboutout {
 /// [`128-bit`]: struct.ParallelHash.html#method.v128
 /// [`256-bit`]: struct.ParallelHash.html#method.v256
 #[derive(Clone)]
-pub struct ParallelHash {
-    state: CShake,
+pub struct ParallelHash<P = KeccakF> {
+    state: CShake<P>,
     block_size: usize,
     bits: u16,
     blocks: usize,
-    unfinished: Option<UnfinishedState>,
+    unfinished: Option<UnfinishedState<P>>,
+}
+
+// Implemented for the default permutation only, like `CShake`. The checkpoint header is followed
+// by the main `cSHAKE` state, the block size, the number of hashed blocks and the state of the
+// unfinished block, if any.
+impl BorshSerialize for ParallelHash {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::ParallelHash, self.bits)?;
+        self.state.serialize(writer)?;
+        (self.block_size as u64).serialize(writer)?;
+        (self.blocks as u64).serialize(writer)?;
+        match self.unfinished {
+            Some(ref unfinished) => {
+                1u8.serialize(writer)?;
+                unfinished.state.serialize(writer)?;
+                (unfinished.absorbed as u64).serialize(writer)
+            }
+            None => 0u8.serialize(writer),
+        }
+    }
+}
+
+impl BorshDeserialize for ParallelHash {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::ParallelHash)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        state.check_absorbing()?;
+        let block_size = read_len(reader)?;
+        if block_size == 0 {
+            return Err(Error::ZeroBlockSize.into());
+        }
+        let blocks = read_len(reader)?;
+        let unfinished = match u8::deserialize_reader(reader)? {
+            0 => None,
+            1 => {
+                let state = CShake::deserialize_reader(reader)?;
+                // the blocks are hashed with cSHAKE without a name, that is SHAKE
+                state.check(bits, 0x1f)?;
+                state.check_absorbing()?;
+                let absorbed = read_len(reader)?;
+                if absorbed == 0 || absorbed >= block_size {
+                    return Err(Error::InvalidState.into());
+                }
+                Some(UnfinishedState { state, absorbed })
+            }
+            _ => return Err(Error::InvalidState.into()),
+        };
+        Ok(ParallelHash {
+            state,
+            block_size,
+            bits,
+            blocks,
+            unfinished,
+        })
+    }
 }
 
 impl ParallelHash {
     /// Creates  new [`ParallelHash`] hasher with a security level of 128 bits.
     ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    ///
     /// [`ParallelHash`]: struct.ParallelHash.html
     pub fn v128(custom_string: &[u8], block_size: usize) -> ParallelHash {
         ParallelHash::new(custom_string, block_size, 128)
@@ -66,25 +133,70 @@ impl ParallelHash {
 
     /// Creates  new [`ParallelHash`] hasher with a security level of 256 bits.
     ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    ///
     /// [`ParallelHash`]: struct.ParallelHash.html
     pub fn v256(custom_string: &[u8], block_size: usize) -> ParallelHash {
         ParallelHash::new(custom_string, block_size, 256)
     }
 
-    fn new(custom_string: &[u8], block_size: usize, bits: u16) -> ParallelHash {
-        let mut state = CShake::new(b"ParallelHash", custom_string, bits);
+    /// Creates  new [`ParallelHash`] hasher with a security level of 128 bits. Fails if
+    /// `block_size` is 0.
+    ///
+    /// [`ParallelHash`]: struct.ParallelHash.html
+    pub fn try_v128(custom_string: &[u8], block_size: usize) -> Result<ParallelHash, Error> {
+        ParallelHash::try_new(custom_string, block_size, 128)
+    }
+
+    /// Creates  new [`ParallelHash`] hasher with a security level of 256 bits. Fails if
+    /// `block_size` is 0.
+    ///
+    /// [`ParallelHash`]: struct.ParallelHash.html
+    pub fn try_v256(custom_string: &[u8], block_size: usize) -> Result<ParallelHash, Error> {
+        ParallelHash::try_new(custom_string, block_size, 256)
+    }
+}
+
+impl<P: Permutation> ParallelHash<P> {
+    /// Creates new [`ParallelHash`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 128 or 256, or if `block_size` is 0.
+    ///
+    /// [`ParallelHash`]: struct.ParallelHash.html
+    pub fn new(custom_string: &[u8], block_size: usize, bits: u16) -> ParallelHash<P> {
+        or_panic(Self::try_new(custom_string, block_size, bits))
+    }
+
+    /// Creates new [`ParallelHash`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 128 or 256, or if `block_size` is 0.
+    ///
+    /// [`ParallelHash`]: struct.ParallelHash.html
+    pub fn try_new(
+        custom_string: &[u8],
+        block_size: usize,
+        bits: u16,
+    ) -> Result<ParallelHash<P>, Error> {
+        if block_size == 0 {
+            return Err(Error::ZeroBlockSize);
+        }
+        let mut state = CShake::try_new(b"ParallelHash", custom_string, bits)?;
         state.update(left_encode(block_size).value());
-        ParallelHash {
+        Ok(ParallelHash {
             state,
             block_size,
             bits,
             blocks: 0,
             unfinished: None,
-        }
+        })
     }
 }
 
-impl Hasher for ParallelHash {
+impl<P: Permutation> Hasher for ParallelHash<P> {
     fn update(&mut self, mut input: &[u8]) {
         if let Some(mut unfinished) = self.unfinished.take() {
             let to_absorb = self.block_size - unfinished.absorbed;
@@ -108,8 +220,24 @@ impl Hasher for ParallelHash {
         let input_blocks_end = input.len() / self.block_size * self.block_size;
         let input_blocks = &input[..input_blocks_end];
         let input_end = &input[input_blocks_end..];
-        let parts = input_blocks.chunks(self.block_size).map(|chunk| {
-            let mut state = CShake::new(b"", b"", bits);
+
+        // four blocks at a time use the four-way permutation
+        let rate = bits_to_rate(bits) as usize;
+        let size = bits as usize / 4;
+        let mut quads = input_blocks.chunks_exact(self.block_size * 4);
+        for quad in &mut quads {
+            let (first, rest) = quad.split_at(self.block_size);
+            let (second, rest) = rest.split_at(self.block_size);
+            let (third, fourth) = rest.split_at(self.block_size);
+            let suboutputs = hash_x4::<P>(rate, 0x1f, [first, second, third, fourth]);
+            for suboutput in suboutputs.iter() {
+                self.state.update(&suboutput[..size]);
+                self.blocks += 1;
+            }
+        }
+
+        let parts = quads.remainder().chunks(self.block_size).map(|chunk| {
+            let mut state = CShake::<P>::new(b"", b"", bits);
             state.update(chunk);
             let mut suboutput = Suboutout::security(bits as usize);
             state.finalize(suboutput.as_bytes_mut());
@@ -123,7 +251,7 @@ impl Hasher for ParallelHash {
 
         if !input_end.is_empty() {
             assert!(self.unfinished.is_none());
-            let mut state = CShake::new(b"", b"", bits);
+            let mut state = CShake::<P>::new(b"", b"", bits);
             state.update(input_end);
             self.unfinished = Some(UnfinishedState {
                 state,
@@ -158,7 +286,7 @@ impl Hasher for ParallelHash {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::{ParallelHash, Xof, IntoXof, Hasher};
+/// # use tari_tiny_keccak::{ParallelHash, Xof, IntoXof, Hasher};
 /// let input = b"hello world";
 /// let mut output = [0u8; 64];
 /// let mut hasher = ParallelHash::v256(b"", 8);
@@ -177,12 +305,28 @@ impl Hasher for ParallelHash {
 /// [`ParallelHashXof`]: struct.ParallelHashXof.html
 /// [`ParallelHash::IntoXof`]: struct.ParallelHash.html#impl-IntoXof
 #[derive(Clone)]
-pub struct ParallelHashXof {
-    state: CShake,
+pub struct ParallelHashXof<P = KeccakF> {
+    state: CShake<P>,
+}
+
+impl BorshSerialize for ParallelHashXof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::ParallelHashXof, self.state.bits())?;
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for ParallelHashXof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::ParallelHashXof)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        Ok(ParallelHashXof { state })
+    }
 }
 
-impl IntoXof for ParallelHash {
-    type Xof = ParallelHashXof;
+impl<P: Permutation> IntoXof for ParallelHash<P> {
+    type Xof = ParallelHashXof<P>;
 
     fn into_xof(mut self) -> Self::Xof {
         if let Some(unfinished) = self.unfinished.take() {
@@ -199,7 +343,7 @@ impl IntoXof for ParallelHash {
     }
 }
 
-impl Xof for ParallelHashXof {
+impl<P: Permutation> Xof for ParallelHashXof<P> {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output);
     }
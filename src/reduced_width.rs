@@ -0,0 +1,142 @@
+//! `keccak-f[b]` permutations for the widths smaller than 1600 bits.
+//!
+//! Every width `b = 25 * w` uses lanes of `w` bits and `12 + 2 * log2(w)` rounds. Round
+//! constants are the ones of `keccak-f[1600]` truncated to `w` bits and rotation offsets are
+//! taken modulo `w`.
+//!
+//! The 25, 50 and 100-bit permutations store every lane in the lowest bits of an `u8`. The
+//! remaining bits of each lane must be zero.
+
+use crate::{Buffer, Permutation, WORDS};
+use core::convert::TryInto;
+
+#[inline(always)]
+fn rotate_left_bits<const W: u32>(lane: u8, n: u32) -> u8 {
+    let mask = (1u8 << W) - 1;
+    let lane = lane & mask;
+    let n = n % W;
+    ((lane << n) | (lane >> (W - n))) & mask
+}
+
+const RC25: [u8; 12] = [0x1, 0x0, 0x0, 0x0, 0x1, 0x1, 0x1, 0x1, 0x0, 0x0, 0x1, 0x0];
+
+const RC50: [u8; 14] = [
+    0x1, 0x2, 0x2, 0x0, 0x3, 0x1, 0x1, 0x1, 0x2, 0x0, 0x1, 0x2, 0x3, 0x3,
+];
+
+const RC100: [u8; 16] = [
+    0x1, 0x2, 0xa, 0x0, 0xb, 0x1, 0x1, 0x9, 0xa, 0x8, 0x9, 0xa, 0xb, 0xb, 0x9, 0x3,
+];
+
+const RC200: [u8; 18] = [
+    0x01, 0x82, 0x8a, 0x00, 0x8b, 0x01, 0x81, 0x09, 0x8a, 0x88, 0x09, 0x0a, 0x8b, 0x8b, 0x89, 0x03,
+    0x02, 0x80,
+];
+
+const RC400: [u16; 20] = [
+    0x0001, 0x8082, 0x808a, 0x8000, 0x808b, 0x0001, 0x8081, 0x8009, 0x008a, 0x0088, 0x8009, 0x000a,
+    0x808b, 0x008b, 0x8089, 0x8003, 0x8002, 0x0080, 0x800a, 0x000a,
+];
+
+const RC800: [u32; 22] = [
+    0x00000001, 0x00008082, 0x0000808a, 0x80008000, 0x0000808b, 0x80000001, 0x80008081, 0x00008009,
+    0x0000008a, 0x00000088, 0x80008009, 0x8000000a, 0x8000808b, 0x0000008b, 0x00008089, 0x00008003,
+    0x00008002, 0x00000080, 0x0000800a, 0x8000000a, 0x80008081, 0x00008080,
+];
+
+keccak_function!(
+    "`keccak-f[25]`",
+    keccakf25,
+    u8,
+    rotate_left_bits::<1>,
+    12,
+    RC25
+);
+
+keccak_function!(
+    "`keccak-f[50]`",
+    keccakf50,
+    u8,
+    rotate_left_bits::<2>,
+    14,
+    RC50
+);
+
+keccak_function!(
+    "`keccak-f[100]`",
+    keccakf100,
+    u8,
+    rotate_left_bits::<4>,
+    16,
+    RC100
+);
+
+keccak_function!(
+    "`keccak-f[200]`",
+    keccakf200,
+    u8,
+    u8::rotate_left,
+    18,
+    RC200
+);
+
+keccak_function!(
+    "`keccak-f[400]`",
+    keccakf400,
+    u16,
+    u16::rotate_left,
+    20,
+    RC400
+);
+
+keccak_function!(
+    "`keccak-f[800]`",
+    keccakf800,
+    u32,
+    u32::rotate_left,
+    22,
+    RC800
+);
+
+macro_rules! reduced_width_permutation {
+    ($doc: expr, $name: ident, $function: ident, $lane: ty) => {
+        #[doc = $doc]
+        ///
+        /// The permutation state is stored in the first `25 * size_of::<lane>()` bytes of the
+        /// sponge state. Its `WIDTH` makes [`Sponge`] and [`Duplex`] reject a rate that is not
+        /// smaller than that.
+        ///
+        /// [`Sponge`]: struct.Sponge.html
+        /// [`Duplex`]: struct.Duplex.html
+        #[derive(Clone, Copy, Debug)]
+        pub struct $name;
+
+        impl Permutation for $name {
+            const WIDTH: usize = WORDS * 8 * core::mem::size_of::<$lane>();
+
+            fn execute(buffer: &mut Buffer) {
+                const LANE: usize = core::mem::size_of::<$lane>();
+                let mut a: [$lane; WORDS] = [0; WORDS];
+                let mut bytes = [0u8; WORDS * LANE];
+                buffer.setout(&mut bytes, 0, WORDS * LANE);
+                for (lane, bytes) in a.iter_mut().zip(bytes.chunks_exact(LANE)) {
+                    *lane = <$lane>::from_le_bytes(bytes.try_into().unwrap());
+                }
+                $function(&mut a);
+                // adding the old and the new state to the buffer overwrites it
+                for (lane, bytes) in a.iter().zip(bytes.chunks_exact_mut(LANE)) {
+                    for (byte, new) in bytes.iter_mut().zip(lane.to_le_bytes().iter()) {
+                        *byte ^= *new;
+                    }
+                }
+                buffer.xorin(&bytes, 0, WORDS * LANE);
+            }
+        }
+    };
+}
+
+reduced_width_permutation!("`keccak-f[200]` permutation.", KeccakF200, keccakf200, u8);
+
+reduced_width_permutation!("`keccak-f[400]` permutation.", KeccakF400, keccakf400, u16);
+
+reduced_width_permutation!("`keccak-f[800]` permutation.", KeccakF800, keccakf800, u32);
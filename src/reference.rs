@@ -0,0 +1,277 @@
+//! Bit-level implementation of [`FIPS-202`] that follows the specification literally.
+//!
+//! The state is an array of bits `A[x][y][z]` and messages are bit strings. Every function is
+//! named after, and written like, the algorithm of the specification it implements. It is very
+//! slow and meant only for checking the other implementations of this crate.
+//!
+//! # Usage
+//!
+//! ```toml
+//! [dependencies]
+//! tiny-keccak = { version = "2.0.0", features = ["reference"] }
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! # use tari_tiny_keccak::reference;
+//! let output = reference::sha3(256, b"abc");
+//! assert_eq!(output[..4], [0x3a, 0x98, 0x5d, 0xa7]);
+//! ```
+//!
+//! [`FIPS-202`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+
+use alloc::vec::Vec;
+
+/// Lane size `w` of `keccak-p[1600]`.
+const W: usize = 64;
+
+/// `l = log2(w)`
+const L: usize = 6;
+
+/// Width `b` of `keccak-p[1600]`.
+const B: usize = 25 * W;
+
+/// The state array `A[x][y][z]`.
+pub type State = [[[bool; W]; 5]; 5];
+
+/// Converts a string of `b` bits to a state array (section 3.1.2).
+pub fn state_from_bits(s: &[bool]) -> State {
+    assert_eq!(s.len(), B, "state must have {} bits", B);
+    let mut a = [[[false; W]; 5]; 5];
+    for (x, sheet) in a.iter_mut().enumerate() {
+        for (y, lane) in sheet.iter_mut().enumerate() {
+            for (z, bit) in lane.iter_mut().enumerate() {
+                *bit = s[W * (5 * y + x) + z];
+            }
+        }
+    }
+    a
+}
+
+/// Converts a state array to a string of `b` bits (section 3.1.3).
+pub fn state_to_bits(a: &State) -> Vec<bool> {
+    let mut s = Vec::with_capacity(B);
+    for y in 0..5 {
+        for sheet in a.iter() {
+            s.extend_from_slice(&sheet[y]);
+        }
+    }
+    s
+}
+
+/// Algorithm 1, `theta(A)`.
+pub fn theta(a: &State) -> State {
+    let mut c = [[false; W]; 5];
+    for (x, column) in c.iter_mut().enumerate() {
+        for (z, bit) in column.iter_mut().enumerate() {
+            *bit = a[x][0][z] ^ a[x][1][z] ^ a[x][2][z] ^ a[x][3][z] ^ a[x][4][z];
+        }
+    }
+    let mut d = [[false; W]; 5];
+    for (x, column) in d.iter_mut().enumerate() {
+        for (z, bit) in column.iter_mut().enumerate() {
+            *bit = c[(x + 4) % 5][z] ^ c[(x + 1) % 5][(z + W - 1) % W];
+        }
+    }
+    let mut result = *a;
+    for (x, sheet) in result.iter_mut().enumerate() {
+        for lane in sheet.iter_mut() {
+            for (z, bit) in lane.iter_mut().enumerate() {
+                *bit ^= d[x][z];
+            }
+        }
+    }
+    result
+}
+
+/// Algorithm 2, `rho(A)`.
+pub fn rho(a: &State) -> State {
+    let mut result = [[[false; W]; 5]; 5];
+    result[0][0] = a[0][0];
+    let (mut x, mut y) = (1, 0);
+    for t in 0..24 {
+        for z in 0..W {
+            result[x][y][z] = a[x][y][(z + W * W - (t + 1) * (t + 2) / 2) % W];
+        }
+        let next = (y, (2 * x + 3 * y) % 5);
+        x = next.0;
+        y = next.1;
+    }
+    result
+}
+
+/// Algorithm 3, `pi(A)`.
+pub fn pi(a: &State) -> State {
+    let mut result = [[[false; W]; 5]; 5];
+    for (x, sheet) in result.iter_mut().enumerate() {
+        for (y, lane) in sheet.iter_mut().enumerate() {
+            *lane = a[(x + 3 * y) % 5][x];
+        }
+    }
+    result
+}
+
+/// Algorithm 4, `chi(A)`.
+pub fn chi(a: &State) -> State {
+    let mut result = [[[false; W]; 5]; 5];
+    for (x, sheet) in result.iter_mut().enumerate() {
+        for (y, lane) in sheet.iter_mut().enumerate() {
+            for (z, bit) in lane.iter_mut().enumerate() {
+                *bit = a[x][y][z] ^ ((a[(x + 1) % 5][y][z] ^ true) & a[(x + 2) % 5][y][z]);
+            }
+        }
+    }
+    result
+}
+
+/// Algorithm 5, `rc(t)`.
+pub fn rc(t: usize) -> bool {
+    let t = t % 255;
+    if t == 0 {
+        return true;
+    }
+    let mut r = Vec::from([true, false, false, false, false, false, false, false]);
+    for _ in 1..=t {
+        r.insert(0, false);
+        r[0] ^= r[8];
+        r[4] ^= r[8];
+        r[5] ^= r[8];
+        r[6] ^= r[8];
+        r.truncate(8);
+    }
+    r[0]
+}
+
+/// Algorithm 6, `iota(A, i_r)`.
+pub fn iota(a: &State, ir: usize) -> State {
+    let mut result = *a;
+    let mut rc_lane = [false; W];
+    for j in 0..=L {
+        rc_lane[(1 << j) - 1] = rc(j + 7 * ir);
+    }
+    for (z, bit) in result[0][0].iter_mut().enumerate() {
+        *bit ^= rc_lane[z];
+    }
+    result
+}
+
+/// `Rnd(A, i_r)`
+pub fn rnd(a: &State, ir: usize) -> State {
+    iota(&chi(&pi(&rho(&theta(a)))), ir)
+}
+
+/// Algorithm 7, `keccak-p[1600, n_r](S)`.
+///
+/// # Panics
+///
+/// Panics if `s` is not 1600 bits long or if `rounds` is greater than 24.
+pub fn keccak_p(s: &[bool], rounds: usize) -> Vec<bool> {
+    assert!(rounds <= 12 + 2 * L, "number of rounds cannot exceed 24");
+    let mut a = state_from_bits(s);
+    for ir in 12 + 2 * L - rounds..12 + 2 * L {
+        a = rnd(&a, ir);
+    }
+    state_to_bits(&a)
+}
+
+/// `keccak-f[1600]` applied to a state of 25 lanes, where bit `z` of lane `x + 5 * y` is
+/// `A[x][y][z]`.
+pub fn keccakf(lanes: &mut [u64; 25]) {
+    let s: Vec<bool> = lanes
+        .iter()
+        .flat_map(|lane| (0..W).map(move |z| lane >> z & 1 == 1))
+        .collect();
+    let s = keccak_p(&s, 12 + 2 * L);
+    for (lane, bits) in lanes.iter_mut().zip(s.chunks(W)) {
+        *lane = bits
+            .iter()
+            .enumerate()
+            .fold(0, |lane, (z, bit)| lane | (*bit as u64) << z);
+    }
+}
+
+/// Algorithm 9, `pad10*1(x, m)`.
+pub fn pad10_1(x: usize, m: usize) -> Vec<bool> {
+    let j = (2 * x - (m + 2) % x) % x;
+    let mut p = Vec::with_capacity(j + 2);
+    p.push(true);
+    p.resize(j + 1, false);
+    p.push(true);
+    p
+}
+
+/// Algorithm 8, `SPONGE[keccak-p[1600, n_r], pad10*1, r](N, d)`.
+///
+/// # Panics
+///
+/// Panics if `r` is not in range `1..1600`.
+pub fn sponge(rounds: usize, r: usize, n: &[bool], d: usize) -> Vec<bool> {
+    assert!(r > 0 && r < B, "rate must be in range 1..1600");
+    let mut p = n.to_vec();
+    p.extend(pad10_1(r, n.len()));
+    let mut s = Vec::from([false; B]);
+    for block in p.chunks(r) {
+        for (bit, p) in s.iter_mut().zip(block) {
+            *bit ^= *p;
+        }
+        s = keccak_p(&s, rounds);
+    }
+    let mut z = Vec::new();
+    loop {
+        z.extend_from_slice(&s[..r]);
+        if d <= z.len() {
+            z.truncate(d);
+            return z;
+        }
+        s = keccak_p(&s, rounds);
+    }
+}
+
+/// `KECCAK[c](N, d)`
+pub fn keccak(c: usize, n: &[bool], d: usize) -> Vec<bool> {
+    sponge(12 + 2 * L, B - c, n, d)
+}
+
+/// Converts bytes to a bit string, least significant bit of every byte first (appendix B.1).
+pub fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).map(move |i| byte >> i & 1 == 1))
+        .collect()
+}
+
+/// Converts a bit string whose length is a multiple of 8 to bytes (appendix B.1).
+///
+/// # Panics
+///
+/// Panics if the length of `bits` is not a multiple of 8.
+pub fn bits_to_bytes(bits: &[bool]) -> Vec<u8> {
+    assert_eq!(
+        bits.len() % 8,
+        0,
+        "bit string must have a whole number of bytes"
+    );
+    bits.chunks(8)
+        .map(|bits| {
+            bits.iter()
+                .enumerate()
+                .fold(0, |byte, (i, bit)| byte | (*bit as u8) << i)
+        })
+        .collect()
+}
+
+/// `SHA3-d(M) = KECCAK[2d](M || 01, d)`
+pub fn sha3(d: usize, m: &[u8]) -> Vec<u8> {
+    let mut n = bytes_to_bits(m);
+    n.extend_from_slice(&[false, true]);
+    bits_to_bytes(&keccak(2 * d, &n, d))
+}
+
+/// `SHAKE128(M, d) = KECCAK[256](M || 1111, d)` and `SHAKE256(M, d) = KECCAK[512](M || 1111, d)`,
+/// where the security level is given by `bits`. `d` is the output length in bytes.
+pub fn shake(bits: usize, m: &[u8], d: usize) -> Vec<u8> {
+    let mut n = bytes_to_bits(m);
+    n.extend_from_slice(&[true, true, true, true]);
+    bits_to_bytes(&keccak(2 * bits, &n, 8 * d))
+}
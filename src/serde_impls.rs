@@ -0,0 +1,248 @@
+//! `serde` support for the states and the hashers.
+//!
+//! A [`Buffer`] is a tuple of its 25 lanes and a [`Mode`] is its Borsh discriminant. The hashers
+//! are serialized as bytes: their Borsh encoding or, for `TurboShake`, the layout described in
+//! `STATE_BYTES`. A deserialized hasher is decoded from these bytes, so it is validated like a
+//! Borsh checkpoint.
+//!
+//! [`Buffer`]: struct.Buffer.html
+//! [`Mode`]: enum.Mode.html
+
+use crate::{Buffer, Mode, WORDS};
+use alloc::vec::Vec;
+use borsh::BorshDeserialize;
+use core::fmt;
+use serde::{de, ser::SerializeTuple, Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "cshake")]
+use crate::CShake;
+#[cfg(feature = "duplex")]
+use crate::Duplex;
+#[cfg(feature = "keccak")]
+use crate::Keccak;
+#[cfg(feature = "sha3")]
+use crate::Sha3;
+#[cfg(feature = "shake")]
+use crate::Shake;
+#[cfg(feature = "sponge")]
+use crate::Sponge;
+#[cfg(feature = "turbo_shake")]
+use crate::{Error, TurboShake, STATE_BYTES};
+#[cfg(feature = "k12")]
+use crate::{
+    KangarooTwelve, KangarooTwelve256, KangarooTwelve256Xof, KangarooTwelveXof,
+    MarsupilamiFourteen, MarsupilamiFourteenXof,
+};
+#[cfg(feature = "kmac")]
+use crate::{Kmac, KmacXof};
+#[cfg(feature = "parallel_hash")]
+use crate::{ParallelHash, ParallelHashXof};
+#[cfg(feature = "tuple_hash")]
+use crate::{TupleHash, TupleHashXof};
+
+impl Serialize for Buffer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(WORDS)?;
+        for word in self.0.iter() {
+            tuple.serialize_element(word)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Buffer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BufferVisitor;
+
+        impl<'de> de::Visitor<'de> for BufferVisitor {
+            type Value = Buffer;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a tuple of {} lanes", WORDS)
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Buffer, A::Error> {
+                let mut buffer = Buffer::default();
+                for (i, word) in buffer.0.iter_mut().enumerate() {
+                    *word = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(buffer)
+            }
+        }
+
+        deserializer.deserialize_tuple(WORDS, BufferVisitor)
+    }
+}
+
+impl Serialize for Mode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mode = <u8 as Deserialize>::deserialize(deserializer)?;
+        Mode::try_from_slice(&[mode]).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "k12",
+    feature = "sponge",
+    feature = "duplex"
+))]
+/// Serializes `value` as the bytes of its Borsh encoding.
+fn serialize_borsh<T: borsh::BorshSerialize, S: Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let bytes = borsh::to_vec(value).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_bytes(&bytes)
+}
+
+/// Deserializes bytes, given as bytes or as a sequence like in JSON, and decodes them with
+/// `decode`.
+fn deserialize_bytes<'de, D, T, E, F>(deserializer: D, decode: F) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    E: fmt::Display,
+    F: FnOnce(&[u8]) -> Result<T, E>,
+{
+    struct BytesVisitor;
+
+    impl<'de> de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("the bytes of a hasher state")
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    let bytes = deserializer.deserialize_bytes(BytesVisitor)?;
+    decode(&bytes).map_err(de::Error::custom)
+}
+
+#[cfg(any(
+    feature = "keccak",
+    feature = "shake",
+    feature = "sha3",
+    feature = "cshake",
+    feature = "k12",
+    feature = "sponge",
+    feature = "duplex"
+))]
+macro_rules! serde_via_borsh {
+    ($($ty:ident),*) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_borsh(self, serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_bytes(deserializer, <$ty>::try_from_slice)
+                }
+            }
+        )*
+    };
+    ($($ty:ident<T>),*) => {
+        $(
+            impl<T: AsRef<[u8]>> Serialize for $ty<T> {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serialize_borsh(self, serializer)
+                }
+            }
+
+            impl<'de, T: From<Vec<u8>>> Deserialize<'de> for $ty<T> {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserialize_bytes(deserializer, <$ty<T>>::try_from_slice)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "keccak")]
+serde_via_borsh!(Keccak);
+
+#[cfg(feature = "sha3")]
+serde_via_borsh!(Sha3);
+
+#[cfg(feature = "shake")]
+serde_via_borsh!(Shake);
+
+#[cfg(feature = "cshake")]
+serde_via_borsh!(CShake);
+
+#[cfg(feature = "kmac")]
+serde_via_borsh!(Kmac, KmacXof);
+
+#[cfg(feature = "tuple_hash")]
+serde_via_borsh!(TupleHash, TupleHashXof);
+
+#[cfg(feature = "parallel_hash")]
+serde_via_borsh!(ParallelHash, ParallelHashXof);
+
+#[cfg(feature = "k12")]
+serde_via_borsh!(
+    KangarooTwelveXof,
+    KangarooTwelve256Xof,
+    MarsupilamiFourteenXof
+);
+
+#[cfg(feature = "k12")]
+serde_via_borsh!(
+    KangarooTwelve<T>,
+    KangarooTwelve256<T>,
+    MarsupilamiFourteen<T>
+);
+
+#[cfg(feature = "sponge")]
+serde_via_borsh!(Sponge);
+
+#[cfg(feature = "duplex")]
+serde_via_borsh!(Duplex);
+
+#[cfg(feature = "turbo_shake")]
+impl Serialize for TurboShake {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "turbo_shake")]
+impl<'de> Deserialize<'de> for TurboShake {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use core::convert::TryInto;
+
+        deserialize_bytes(deserializer, |bytes| {
+            let bytes: &[u8; STATE_BYTES] = bytes.try_into().map_err(|_| Error::InvalidState)?;
+            TurboShake::from_bytes(bytes)
+        })
+    }
+}
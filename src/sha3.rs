@@ -1,4 +1,8 @@
-use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
+use crate::{
+    hash_short, keccakf::KeccakF, or_panic, security_level_to_rate, Error, Hasher, KeccakState,
+    Permutation, STATE_BYTES,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 /// The `SHA3` hash functions defined in [`FIPS-202`].
 ///
@@ -14,7 +18,7 @@ use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::{Hasher, Sha3};
+/// # use tari_tiny_keccak::{Hasher, Sha3};
 /// #
 /// # fn main() {
 /// let input = b"hello world";
@@ -30,13 +34,26 @@ use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState};
 /// # }
 /// ```
 #[derive(Clone)]
-pub struct Sha3 {
-    state: KeccakState<KeccakF>,
+pub struct Sha3<P = KeccakF> {
+    state: KeccakState<P>,
 }
 
-impl Sha3 {
-    const DELIM: u8 = 0x06;
+// Default permutation only, like `Keccak`.
+impl BorshSerialize for Sha3 {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for Sha3 {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Sha3::from_state(KeccakState::deserialize_with_delim(
+            reader,
+        )?)?)
+    }
+}
 
+impl Sha3 {
     /// Creates  new [`Sha3`] hasher with a security level of 224 bits.
     ///
     /// [`Sha3`]: struct.Sha3.html
@@ -64,15 +81,71 @@ impl Sha3 {
     pub fn v512() -> Sha3 {
         Sha3::new(512)
     }
+}
+
+impl<P: Permutation> Sha3<P> {
+    const DELIM: u8 = 0x06;
 
-    fn new(bits: u16) -> Sha3 {
-        Sha3 {
-            state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
-        }
+    /// Creates new [`Sha3`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 224, 256, 384 or 512.
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    pub fn new(bits: u16) -> Sha3<P> {
+        or_panic(Self::try_new(bits))
+    }
+
+    /// Creates new [`Sha3`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 224, 256, 384 or 512.
+    ///
+    /// [`Sha3`]: struct.Sha3.html
+    pub fn try_new(bits: u16) -> Result<Sha3<P>, Error> {
+        let rate = security_level_to_rate(bits, &[224, 256, 384, 512])?;
+        Ok(Sha3 {
+            state: KeccakState::new(rate, Self::DELIM),
+        })
+    }
+
+    /// Encodes the state of the hasher in the layout described in [`STATE_BYTES`].
+    ///
+    /// [`STATE_BYTES`]: constant.STATE_BYTES.html
+    pub fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        self.state.to_bytes()
+    }
+
+    /// Decodes a state encoded by [`to_bytes`]. Fails with [`Error::InvalidState`] if `bytes`
+    /// is not the state of a [`Sha3`] hasher.
+    ///
+    /// [`to_bytes`]: struct.Sha3.html#method.to_bytes
+    /// [`Sha3`]: struct.Sha3.html
+    /// [`Error::InvalidState`]: enum.Error.html#variant.InvalidState
+    pub fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<Sha3<P>, Error> {
+        Self::from_state(KeccakState::from_bytes(bytes)?)
+    }
+
+    /// Wraps a decoded state, see `KeccakState::validate`.
+    fn from_state(state: KeccakState<P>) -> Result<Sha3<P>, Error> {
+        state.validate(&[224, 256, 384, 512], |delim| delim == Self::DELIM)?;
+        Ok(Sha3 { state })
+    }
+
+    /// Absorbs the first `bit_len` bits of `input`, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is shorter than `bit_len` bits, or if trailing bits that don't fill a
+    /// byte have already been absorbed.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        self.state.update_bits(input, bit_len);
     }
 }
 
-impl Hasher for Sha3 {
+impl<P: Permutation> Hasher for Sha3<P> {
     fn update(&mut self, input: &[u8]) {
         self.state.update(input);
     }
@@ -81,3 +154,36 @@ impl Hasher for Sha3 {
         self.state.finalize(output);
     }
 }
+
+/// Computes SHA3-256 of `input` with a single permutation. Faster than [`Sha3::v256`] for keys,
+/// digests and other short messages of a fixed length.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["sha3"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{sha3_256_short, Hasher, Sha3};
+/// let mut output = [0u8; 32];
+/// let mut sha3 = Sha3::v256();
+/// sha3.update(b"hello world");
+/// sha3.finalize(&mut output);
+/// assert_eq!(sha3_256_short(b"hello world"), output);
+/// ```
+///
+/// The input must be shorter than the rate of 136 bytes, or the call doesn't compile:
+///
+/// ```compile_fail
+/// # use tari_tiny_keccak::sha3_256_short;
+/// sha3_256_short(&[0u8; 136]);
+/// ```
+///
+/// [`Sha3::v256`]: struct.Sha3.html#method.v256
+pub fn sha3_256_short<const N: usize>(input: &[u8; N]) -> [u8; 32] {
+    hash_short::<N, 136, 32>(input, Sha3::<KeccakF>::DELIM)
+}
@@ -1,4 +1,8 @@
-use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState, Xof};
+use crate::{
+    keccakf::KeccakF, or_panic, security_level_to_rate, Error, Hasher, KeccakState, Permutation,
+    Xof, STATE_BYTES,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 /// The `SHAKE` extendable-output functions defined in [`FIPS-202`].
 ///
@@ -11,13 +15,26 @@ use crate::{bits_to_rate, keccakf::KeccakF, Hasher, KeccakState, Xof};
 ///
 /// [`FIPS-202`]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
 #[derive(Clone)]
-pub struct Shake {
-    state: KeccakState<KeccakF>,
+pub struct Shake<P = KeccakF> {
+    state: KeccakState<P>,
 }
 
-impl Shake {
-    const DELIM: u8 = 0x1f;
+// Default permutation only, like `Keccak`.
+impl BorshSerialize for Shake {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize_with_delim(writer)
+    }
+}
+
+impl BorshDeserialize for Shake {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(Shake::from_state(KeccakState::deserialize_with_delim(
+            reader,
+        )?)?)
+    }
+}
 
+impl Shake {
     /// Creates  new [`Shake`] hasher with a security level of 128 bits.
     ///
     /// [`Shake`]: struct.Shake.html
@@ -31,15 +48,82 @@ impl Shake {
     pub fn v256() -> Shake {
         Shake::new(256)
     }
+}
+
+impl<P: Permutation> Shake<P> {
+    const DELIM: u8 = 0x1f;
+
+    /// Creates new [`Shake`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 128 or 256.
+    ///
+    /// [`Shake`]: struct.Shake.html
+    pub fn new(bits: u16) -> Shake<P> {
+        or_panic(Self::try_new(bits))
+    }
+
+    /// Creates new [`Shake`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 128 or 256.
+    ///
+    /// [`Shake`]: struct.Shake.html
+    pub fn try_new(bits: u16) -> Result<Shake<P>, Error> {
+        let rate = security_level_to_rate(bits, &[128, 256])?;
+        Ok(Shake {
+            state: KeccakState::new(rate, Self::DELIM),
+        })
+    }
+
+    /// Encodes the state of the hasher in the layout described in [`STATE_BYTES`].
+    ///
+    /// [`STATE_BYTES`]: constant.STATE_BYTES.html
+    pub fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        self.state.to_bytes()
+    }
 
-    pub(crate) fn new(bits: u16) -> Shake {
-        Shake {
-            state: KeccakState::new(bits_to_rate(bits), Self::DELIM),
-        }
+    /// Decodes a state encoded by [`to_bytes`]. Fails with [`Error::InvalidState`] if `bytes`
+    /// is not the state of a [`Shake`] hasher.
+    ///
+    /// [`to_bytes`]: struct.Shake.html#method.to_bytes
+    /// [`Shake`]: struct.Shake.html
+    /// [`Error::InvalidState`]: enum.Error.html#variant.InvalidState
+    pub fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<Shake<P>, Error> {
+        Self::from_state(KeccakState::from_bytes(bytes)?)
+    }
+
+    /// Wraps a decoded state, see `KeccakState::validate`.
+    fn from_state(state: KeccakState<P>) -> Result<Shake<P>, Error> {
+        state.validate(&[128, 256], |delim| delim == Self::DELIM)?;
+        Ok(Shake { state })
+    }
+
+    /// Absorbs the first `bit_len` bits of `input`, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is shorter than `bit_len` bits, or if trailing bits that don't fill a
+    /// byte have already been absorbed.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn update_bits(&mut self, input: &[u8], bit_len: usize) {
+        self.state.update_bits(input, bit_len);
+    }
+
+    /// Fills `output` with the next `bit_len` bits of the output, see [bit-oriented messages].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output` is shorter than `bit_len` bits.
+    ///
+    /// [bit-oriented messages]: index.html#bit-oriented-messages
+    pub fn squeeze_bits(&mut self, output: &mut [u8], bit_len: usize) {
+        self.state.squeeze_bits(output, bit_len);
     }
 }
 
-impl Hasher for Shake {
+impl<P: Permutation> Hasher for Shake<P> {
     fn update(&mut self, input: &[u8]) {
         self.state.update(input);
     }
@@ -49,7 +133,7 @@ impl Hasher for Shake {
     }
 }
 
-impl Xof for Shake {
+impl<P: Permutation> Xof for Shake<P> {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
@@ -0,0 +1,243 @@
+//! Sponges with a custom capacity, domain separation suffix and output mode.
+
+use crate::{
+    capacity_to_rate, keccakf::KeccakF, or_panic, Error, Hasher, KeccakState, Mode, Permutation,
+    Xof,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
+use core::marker::PhantomData;
+
+/// How the output of a [`Sponge`] is read.
+///
+/// [`Sponge`]: struct.Sponge.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum OutputMode {
+    /// A hash function with an output of the given number of bytes, read with
+    /// [`Hasher::finalize`].
+    ///
+    /// [`Hasher::finalize`]: trait.Hasher.html#tymethod.finalize
+    Fixed(usize),
+    /// An extendable-output function, read with [`Hasher::finalize`] or [`Xof::squeeze`].
+    ///
+    /// [`Hasher::finalize`]: trait.Hasher.html#tymethod.finalize
+    /// [`Xof::squeeze`]: trait.Xof.html#tymethod.squeeze
+    Extendable,
+}
+
+/// Builder of a [`Sponge`].
+///
+/// By default it builds `Keccak[c=512]` without a suffix, the sponge of [`Keccak::v256`], as an
+/// extendable-output function.
+///
+/// [`Sponge`]: struct.Sponge.html
+/// [`Keccak::v256`]: struct.Keccak.html#method.v256
+#[derive(Clone, Copy, Debug)]
+pub struct SpongeBuilder<P = KeccakF> {
+    capacity: usize,
+    suffix: u8,
+    suffix_len: u8,
+    output: OutputMode,
+    permutation: PhantomData<P>,
+}
+
+impl Default for SpongeBuilder {
+    fn default() -> SpongeBuilder {
+        SpongeBuilder {
+            capacity: 512,
+            suffix: 0,
+            suffix_len: 0,
+            output: OutputMode::Extendable,
+            permutation: PhantomData,
+        }
+    }
+}
+
+impl<P: Permutation> SpongeBuilder<P> {
+    /// Sets the capacity `c` of the sponge in bits. The rate is `b - c` bits, where `b` is the
+    /// [`WIDTH`] of the permutation.
+    ///
+    /// [`WIDTH`]: trait.Permutation.html#associatedconstant.WIDTH
+    pub fn capacity(mut self, bits: usize) -> SpongeBuilder<P> {
+        self.capacity = bits;
+        self
+    }
+
+    /// Sets the domain separation suffix appended to the message before padding. The suffix is
+    /// made of the `len` lowest bits of `bits`, least significant bit first, like the `01` of
+    /// `SHA3` which is `suffix(0b10, 2)`.
+    pub fn suffix(mut self, bits: u8, len: u8) -> SpongeBuilder<P> {
+        self.suffix = bits;
+        self.suffix_len = len;
+        self
+    }
+
+    /// Sets how the output of the sponge is read.
+    pub fn output(mut self, mode: OutputMode) -> SpongeBuilder<P> {
+        self.output = mode;
+        self
+    }
+
+    /// Makes the sponge use the permutation `Q`.
+    pub fn permutation<Q: Permutation>(self) -> SpongeBuilder<Q> {
+        SpongeBuilder {
+            capacity: self.capacity,
+            suffix: self.suffix,
+            suffix_len: self.suffix_len,
+            output: self.output,
+            permutation: PhantomData,
+        }
+    }
+
+    /// Creates the [`Sponge`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rate is not a whole number of bytes, of at least 2 bytes and smaller than the
+    /// width of the permutation, or if the suffix is longer than 6 bits or doesn't fit in its
+    /// length.
+    ///
+    /// [`Sponge`]: struct.Sponge.html
+    pub fn build(self) -> Sponge<P> {
+        or_panic(self.try_build())
+    }
+
+    /// Creates the [`Sponge`]. Fails if the rate is not a whole number of bytes, of at least 2
+    /// bytes and smaller than the width of the permutation, or if the suffix is longer than 6 bits
+    /// or doesn't fit in its length.
+    ///
+    /// [`Sponge`]: struct.Sponge.html
+    pub fn try_build(self) -> Result<Sponge<P>, Error> {
+        let rate = capacity_to_rate(self.capacity, P::WIDTH)?;
+        if self.suffix_len > 6 {
+            return Err(Error::SuffixTooLong);
+        }
+        if self.suffix >> self.suffix_len != 0 {
+            return Err(Error::SuffixOverflow(self.suffix_len));
+        }
+        let delim = self.suffix | 1 << self.suffix_len;
+        Ok(Sponge {
+            state: KeccakState::new(rate, delim),
+            output: self.output,
+        })
+    }
+}
+
+/// A sponge with a custom capacity, domain separation suffix and output mode, built with
+/// [`SpongeBuilder`].
+///
+/// It can express every `Keccak[c]` based function of this crate, as well as variants that are
+/// not covered by the other types.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["sponge"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{Hasher, OutputMode, Sponge};
+/// // the legacy Keccak-288
+/// let mut keccak288 = Sponge::builder()
+///     .capacity(576)
+///     .output(OutputMode::Fixed(36))
+///     .build();
+/// let mut output = [0u8; 36];
+/// keccak288.update(b"hello world");
+/// keccak288.finalize(&mut output);
+/// ```
+///
+/// [`SpongeBuilder`]: struct.SpongeBuilder.html
+#[derive(Clone)]
+pub struct Sponge<P = KeccakF> {
+    state: KeccakState<P>,
+    output: OutputMode,
+}
+
+// Implemented for the default permutation only, like `Keccak`. The state and its suffix are
+// followed by the output mode.
+impl BorshSerialize for Sponge {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.state.serialize_with_delim(writer)?;
+        self.output.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for Sponge {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let state = KeccakState::deserialize_with_delim(reader)?;
+        let output = OutputMode::deserialize_reader(reader)?;
+        // the suffix is at most 6 bits long, and a sponge with a fixed output is never squeezed
+        let squeezed = matches!(state.mode, Mode::Squeezing);
+        if state.delim >= 0x80 || (squeezed && output != OutputMode::Extendable) {
+            return Err(Error::InvalidState.into());
+        }
+        Ok(Sponge { state, output })
+    }
+}
+
+impl Sponge {
+    /// Creates new [`SpongeBuilder`] with its default settings.
+    ///
+    /// [`SpongeBuilder`]: struct.SpongeBuilder.html
+    pub fn builder() -> SpongeBuilder {
+        SpongeBuilder::default()
+    }
+}
+
+impl<P: Permutation> Sponge<P> {
+    /// The rate of the sponge in bytes.
+    pub fn rate(&self) -> usize {
+        self.state.rate as usize
+    }
+
+    /// The capacity of the sponge in bits.
+    pub fn capacity(&self) -> usize {
+        P::WIDTH - self.rate() * 8
+    }
+
+    /// How the output of the sponge is read.
+    pub fn output_mode(&self) -> OutputMode {
+        self.output
+    }
+}
+
+impl<P: Permutation> Hasher for Sponge<P> {
+    fn update(&mut self, input: &[u8]) {
+        self.state.update(input);
+    }
+
+    /// Pad and squeeze the state to the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output mode is [`OutputMode::Fixed`] and `output` doesn't have its length.
+    ///
+    /// [`OutputMode::Fixed`]: enum.OutputMode.html#variant.Fixed
+    fn finalize(self, output: &mut [u8]) {
+        if let OutputMode::Fixed(len) = self.output {
+            assert_eq!(output.len(), len, "output must be {} bytes long", len);
+        }
+        self.state.finalize(output);
+    }
+}
+
+impl<P: Permutation> Xof for Sponge<P> {
+    /// A method used to retrieve another part of hash function output.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the output mode is [`OutputMode::Extendable`].
+    ///
+    /// [`OutputMode::Extendable`]: enum.OutputMode.html#variant.Extendable
+    fn squeeze(&mut self, output: &mut [u8]) {
+        assert_eq!(
+            self.output,
+            OutputMode::Extendable,
+            "only an extendable-output sponge can be squeezed"
+        );
+        self.state.squeeze(output);
+    }
+}
@@ -0,0 +1,263 @@
+//! The step mappings of `keccak-p[1600]` and their inverses.
+//!
+//! The state is indexed by `x + 5 * y`. One round of `keccak-p[1600]` is `theta`, `rho`, `pi`,
+//! `chi` and `iota`, applied in this order.
+
+use crate::{keccakf::RC, WORDS};
+
+/// Rotation offsets of `rho`, indexed by `x + 5 * y`.
+const RHO_OFFSETS: [u32; WORDS] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Inverse of the map `C[x] ^= C[x - 1] ^ rotate_left(C[x + 1], 1)` that `theta` applies to the
+/// column parities. Bit `z` of `THETA_INVERSE[i]` means that the parity of column `x - i` rotated
+/// left by `z` contributes to the parity of column `x`.
+const THETA_INVERSE: [u64; 5] = [
+    0xde26bc4d789af135,
+    0x09af135e26bc4d78,
+    0xebc4d789af135e26,
+    0x7135e26bc4d789af,
+    0xcd789af135e26bc4,
+];
+
+fn column_parities(a: &[u64; WORDS]) -> [u64; 5] {
+    let mut parities = [0u64; 5];
+    for (x, parity) in parities.iter_mut().enumerate() {
+        for y in (0..WORDS).step_by(5) {
+            *parity ^= a[x + y];
+        }
+    }
+    parities
+}
+
+fn add_column_effect(a: &mut [u64; WORDS], parities: &[u64; 5]) {
+    for x in 0..5 {
+        let effect = parities[(x + 4) % 5] ^ parities[(x + 1) % 5].rotate_left(1);
+        for y in (0..WORDS).step_by(5) {
+            a[x + y] ^= effect;
+        }
+    }
+}
+
+/// The `theta` step mapping.
+pub fn theta(a: &mut [u64; WORDS]) {
+    let parities = column_parities(a);
+    add_column_effect(a, &parities);
+}
+
+/// Inverse of [`theta`].
+///
+/// [`theta`]: fn.theta.html
+pub fn theta_inverse(a: &mut [u64; WORDS]) {
+    let output = column_parities(a);
+    let mut parities = [0u64; 5];
+    for (x, parity) in parities.iter_mut().enumerate() {
+        for (i, mask) in THETA_INVERSE.iter().enumerate() {
+            for z in (0..64).filter(|z| mask >> z & 1 == 1) {
+                *parity ^= output[(x + 5 - i) % 5].rotate_left(z);
+            }
+        }
+    }
+    add_column_effect(a, &parities);
+}
+
+/// The `rho` step mapping.
+pub fn rho(a: &mut [u64; WORDS]) {
+    for (lane, offset) in a.iter_mut().zip(RHO_OFFSETS.iter()) {
+        *lane = lane.rotate_left(*offset);
+    }
+}
+
+/// Inverse of [`rho`].
+///
+/// [`rho`]: fn.rho.html
+pub fn rho_inverse(a: &mut [u64; WORDS]) {
+    for (lane, offset) in a.iter_mut().zip(RHO_OFFSETS.iter()) {
+        *lane = lane.rotate_right(*offset);
+    }
+}
+
+/// The `pi` step mapping.
+pub fn pi(a: &mut [u64; WORDS]) {
+    let b = *a;
+    for x in 0..5 {
+        for y in 0..5 {
+            a[x + 5 * y] = b[(x + 3 * y) % 5 + 5 * x];
+        }
+    }
+}
+
+/// Inverse of [`pi`].
+///
+/// [`pi`]: fn.pi.html
+pub fn pi_inverse(a: &mut [u64; WORDS]) {
+    let b = *a;
+    for x in 0..5 {
+        for y in 0..5 {
+            a[(x + 3 * y) % 5 + 5 * x] = b[x + 5 * y];
+        }
+    }
+}
+
+/// The `chi` step mapping.
+pub fn chi(a: &mut [u64; WORDS]) {
+    for y in (0..WORDS).step_by(5) {
+        let mut row = [0u64; 5];
+        row.copy_from_slice(&a[y..y + 5]);
+        for x in 0..5 {
+            a[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+        }
+    }
+}
+
+/// Inverse of [`chi`].
+///
+/// [`chi`]: fn.chi.html
+pub fn chi_inverse(a: &mut [u64; WORDS]) {
+    for y in (0..WORDS).step_by(5) {
+        let mut row = [0u64; 5];
+        row.copy_from_slice(&a[y..y + 5]);
+        // solving the lanes in the order 0, 3, 1, 4, 2 and 0 again inverts the row
+        for i in 0..6 {
+            let x = 3 * i % 5;
+            a[y + x] = row[x] ^ (!a[y + (x + 1) % 5] & a[y + (x + 2) % 5]);
+        }
+    }
+}
+
+/// The `iota` step mapping of round `round` of `keccak-f[1600]`. The last `n_r` rounds of
+/// `keccak-f[1600]` form `keccak-p[1600, n_r]`.
+///
+/// # Panics
+///
+/// Panics if `round` is greater than 23.
+pub fn iota(a: &mut [u64; WORDS], round: usize) {
+    a[0] ^= RC[round];
+}
+
+/// Inverse of [`iota`], which is `iota` itself.
+///
+/// # Panics
+///
+/// Panics if `round` is greater than 23.
+///
+/// [`iota`]: fn.iota.html
+pub fn iota_inverse(a: &mut [u64; WORDS], round: usize) {
+    iota(a, round);
+}
+
+/// Inverse of `keccak-f[1600, 24]`.
+pub fn keccakf_inverse(a: &mut [u64; WORDS]) {
+    keccak_p_inverse(a, RC.len());
+}
+
+/// Inverse of `keccak-p[1600, n_r]`, where `n_r` is given by `rounds`.
+///
+/// # Panics
+///
+/// Panics if `rounds` is greater than 24.
+pub fn keccak_p_inverse(a: &mut [u64; WORDS], rounds: usize) {
+    assert!(
+        rounds <= RC.len(),
+        "number of rounds cannot exceed {}",
+        RC.len()
+    );
+    for round in (RC.len() - rounds..RC.len()).rev() {
+        iota_inverse(a, round);
+        chi_inverse(a);
+        pi_inverse(a);
+        rho_inverse(a);
+        theta_inverse(a);
+    }
+}
+
+/// A step mapping of `keccak-p[1600]`, as reported to the callback of [`keccakf_traced`].
+///
+/// [`keccakf_traced`]: fn.keccakf_traced.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// `theta`
+    Theta,
+    /// `rho`
+    Rho,
+    /// `pi`
+    Pi,
+    /// `chi`
+    Chi,
+    /// `iota`
+    Iota,
+}
+
+impl Step {
+    /// Name of the step, as printed in the `After <name>:` lines of the intermediate values
+    /// published by the Keccak team.
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::Theta => "theta",
+            Step::Rho => "rho",
+            Step::Pi => "pi",
+            Step::Chi => "chi",
+            Step::Iota => "iota",
+        }
+    }
+}
+
+/// `keccak-f[1600, 24]` that calls `trace` with the round number, the step and the state after
+/// every step of every round.
+///
+/// This is a slow implementation built on top of the step mappings, meant for comparing
+/// intermediate states with other implementations. Rounds are numbered from 0 to 23, like in the
+/// intermediate values published by the Keccak team.
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{keccakf, keccakf_traced, Step};
+/// let mut states = [[0u64; 25]; 24 * 5];
+/// let mut state = [0u64; 25];
+/// let mut step = 0;
+/// keccakf_traced(&mut state, |_round, _step: Step, a| {
+///     states[step] = *a;
+///     step += 1;
+/// });
+///
+/// let mut expected = [0u64; 25];
+/// keccakf(&mut expected);
+/// assert_eq!(expected, state);
+/// assert_eq!(expected, states[24 * 5 - 1]);
+/// ```
+pub fn keccakf_traced<F: FnMut(usize, Step, &[u64; WORDS])>(a: &mut [u64; WORDS], trace: F) {
+    keccak_p_traced(a, RC.len(), trace);
+}
+
+/// `keccak-p[1600, n_r]`, where `n_r` is given by `rounds`, that calls `trace` with the round
+/// number, the step and the state after every step of every round. Rounds are numbered like the
+/// rounds of `keccak-f[1600]` they are taken from, from `24 - rounds` to 23.
+///
+/// # Panics
+///
+/// Panics if `rounds` is greater than 24.
+pub fn keccak_p_traced<F: FnMut(usize, Step, &[u64; WORDS])>(
+    a: &mut [u64; WORDS],
+    rounds: usize,
+    mut trace: F,
+) {
+    assert!(
+        rounds <= RC.len(),
+        "number of rounds cannot exceed {}",
+        RC.len()
+    );
+    for round in RC.len() - rounds..RC.len() {
+        theta(a);
+        trace(round, Step::Theta, a);
+        rho(a);
+        trace(round, Step::Rho, a);
+        pi(a);
+        trace(round, Step::Pi, a);
+        chi(a);
+        trace(round, Step::Chi, a);
+        iota(a, round);
+        trace(round, Step::Iota, a);
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
+use crate::{
+    checkpoint::{read_header, write_header, Algorithm},
+    keccakf::KeccakF,
+    left_encode, or_panic, right_encode, CShake, Error, Hasher, IntoXof, Permutation, Xof,
+};
+use borsh::{io, BorshDeserialize, BorshSerialize};
 
 /// The `TupleHash` hash functions defined in [`SP800-185`].
 ///
@@ -17,8 +22,27 @@ use crate::{left_encode, right_encode, CShake, Hasher, IntoXof, Xof};
 ///
 /// [`SP800-185`]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
 #[derive(Clone)]
-pub struct TupleHash {
-    state: CShake,
+pub struct TupleHash<P = KeccakF> {
+    state: CShake<P>,
+}
+
+// Implemented for the default permutation only, like `CShake`. The `cSHAKE` state follows a
+// checkpoint header, see the `checkpoint` module.
+impl BorshSerialize for TupleHash {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::TupleHash, self.state.bits())?;
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for TupleHash {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::TupleHash)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        state.check_absorbing()?;
+        Ok(TupleHash { state })
+    }
 }
 
 impl TupleHash {
@@ -35,15 +59,33 @@ impl TupleHash {
     pub fn v256(custom_string: &[u8]) -> TupleHash {
         TupleHash::new(custom_string, 256)
     }
+}
 
-    fn new(custom_string: &[u8], bits: u16) -> TupleHash {
-        TupleHash {
-            state: CShake::new(b"TupleHash", custom_string, bits),
-        }
+impl<P: Permutation> TupleHash<P> {
+    /// Creates new [`TupleHash`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 128 or 256.
+    ///
+    /// [`TupleHash`]: struct.TupleHash.html
+    pub fn new(custom_string: &[u8], bits: u16) -> TupleHash<P> {
+        or_panic(Self::try_new(custom_string, bits))
+    }
+
+    /// Creates new [`TupleHash`] hasher with a security level of `bits` bits, that uses the
+    /// permutation `P`. Fails if `bits` is not 128 or 256.
+    ///
+    /// [`TupleHash`]: struct.TupleHash.html
+    pub fn try_new(custom_string: &[u8], bits: u16) -> Result<TupleHash<P>, Error> {
+        Ok(TupleHash {
+            state: CShake::try_new(b"TupleHash", custom_string, bits)?,
+        })
     }
 }
 
-impl Hasher for TupleHash {
+impl<P: Permutation> Hasher for TupleHash<P> {
     fn update(&mut self, input: &[u8]) {
         self.state.update(left_encode(input.len() * 8).value());
         self.state.update(input)
@@ -67,7 +109,7 @@ impl Hasher for TupleHash {
 /// # Example
 ///
 /// ```
-/// # use tiny_keccak::{TupleHash, Xof, IntoXof, Hasher};
+/// # use tari_tiny_keccak::{TupleHash, Xof, IntoXof, Hasher};
 /// let input = b"hello world";
 /// let mut output = [0u8; 64];
 /// let mut hasher = TupleHash::v256(b"");
@@ -86,20 +128,36 @@ impl Hasher for TupleHash {
 /// [`TupleHashXof`]: struct.TupleHashXof.html
 /// [`TupleHash::IntoXof`]: struct.TupleHash.html#impl-IntoXof
 #[derive(Clone)]
-pub struct TupleHashXof {
-    state: CShake,
+pub struct TupleHashXof<P = KeccakF> {
+    state: CShake<P>,
+}
+
+impl BorshSerialize for TupleHashXof {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_header(writer, Algorithm::TupleHashXof, self.state.bits())?;
+        self.state.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for TupleHashXof {
+    fn deserialize_reader<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let bits = read_header(reader, Algorithm::TupleHashXof)?;
+        let state = CShake::deserialize_reader(reader)?;
+        state.check(bits, 0x04)?;
+        Ok(TupleHashXof { state })
+    }
 }
 
-impl IntoXof for TupleHash {
-    type Xof = TupleHashXof;
+impl<P: Permutation> IntoXof for TupleHash<P> {
+    type Xof = TupleHashXof<P>;
 
-    fn into_xof(mut self) -> TupleHashXof {
+    fn into_xof(mut self) -> TupleHashXof<P> {
         self.state.update(right_encode(0).value());
         TupleHashXof { state: self.state }
     }
 }
 
-impl Xof for TupleHashXof {
+impl<P: Permutation> Xof for TupleHashXof<P> {
     fn squeeze(&mut self, output: &mut [u8]) {
         self.state.squeeze(output)
     }
@@ -0,0 +1,131 @@
+//! The `TurboSHAKE` extendable-output functions defined in [`RFC 9861`].
+//!
+//! [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
+
+use crate::{
+    bits_to_rate, keccakp::KeccakP, or_panic, Error, Hasher, KeccakState, Xof, STATE_BYTES,
+};
+
+/// The `TurboSHAKE` extendable-output functions defined in [`RFC 9861`].
+///
+/// `TurboSHAKE` is a sponge built on top of `keccak-p[1600, 12]`. It has the same rate and
+/// capacity as [`SHAKE`], but uses half the number of rounds. The domain separation byte must be
+/// in range `0x01..=0x7F`.
+///
+/// # Usage
+///
+/// ```toml
+/// [dependencies]
+/// tiny-keccak = { version = "2.0.0", features = ["turbo_shake"] }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # use tari_tiny_keccak::{Hasher, TurboShake, Xof};
+/// let input = b"hello world";
+/// let mut output = [0u8; 64];
+/// let mut hasher = TurboShake::v128(0x1f);
+/// hasher.update(input);
+/// hasher.squeeze(&mut output[..32]);
+/// hasher.squeeze(&mut output[32..]);
+/// ```
+///
+/// [`RFC 9861`]: https://www.rfc-editor.org/rfc/rfc9861.html
+/// [`SHAKE`]: struct.Shake.html
+#[derive(Clone)]
+pub struct TurboShake {
+    state: KeccakState<KeccakP>,
+}
+
+impl TurboShake {
+    /// Creates  new [`TurboShake`] hasher with a security level of 128 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `domain_byte` is not in range `0x01..=0x7F`.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn v128(domain_byte: u8) -> TurboShake {
+        TurboShake::new(domain_byte, 128)
+    }
+
+    /// Creates  new [`TurboShake`] hasher with a security level of 256 bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `domain_byte` is not in range `0x01..=0x7F`.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn v256(domain_byte: u8) -> TurboShake {
+        TurboShake::new(domain_byte, 256)
+    }
+
+    /// Creates  new [`TurboShake`] hasher with a security level of 128 bits. Fails if
+    /// `domain_byte` is not in range `0x01..=0x7F`.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn try_v128(domain_byte: u8) -> Result<TurboShake, Error> {
+        TurboShake::try_new(domain_byte, 128)
+    }
+
+    /// Creates  new [`TurboShake`] hasher with a security level of 256 bits. Fails if
+    /// `domain_byte` is not in range `0x01..=0x7F`.
+    ///
+    /// [`TurboShake`]: struct.TurboShake.html
+    pub fn try_v256(domain_byte: u8) -> Result<TurboShake, Error> {
+        TurboShake::try_new(domain_byte, 256)
+    }
+
+    pub(crate) fn new(domain_byte: u8, bits: u16) -> TurboShake {
+        or_panic(TurboShake::try_new(domain_byte, bits))
+    }
+
+    fn try_new(domain_byte: u8, bits: u16) -> Result<TurboShake, Error> {
+        if !(0x01..=0x7f).contains(&domain_byte) {
+            return Err(Error::InvalidDomainByte(domain_byte));
+        }
+        Ok(TurboShake {
+            state: KeccakState::new(bits_to_rate(bits), domain_byte),
+        })
+    }
+
+    /// Encodes the state of the hasher in the layout described in [`STATE_BYTES`].
+    ///
+    /// [`STATE_BYTES`]: constant.STATE_BYTES.html
+    pub fn to_bytes(&self) -> [u8; STATE_BYTES] {
+        self.state.to_bytes()
+    }
+
+    /// Decodes a state encoded by [`to_bytes`]. Fails with [`Error::InvalidState`] if `bytes`
+    /// is not the state of a [`TurboShake`] hasher.
+    ///
+    /// [`to_bytes`]: struct.TurboShake.html#method.to_bytes
+    /// [`TurboShake`]: struct.TurboShake.html
+    /// [`Error::InvalidState`]: enum.Error.html#variant.InvalidState
+    pub fn from_bytes(bytes: &[u8; STATE_BYTES]) -> Result<TurboShake, Error> {
+        Self::from_state(KeccakState::from_bytes(bytes)?)
+    }
+
+    /// Wraps a decoded state, see `KeccakState::validate`.
+    fn from_state(state: KeccakState<KeccakP>) -> Result<TurboShake, Error> {
+        state.validate(&[128, 256], |delim| (0x01..=0x7f).contains(&delim))?;
+        Ok(TurboShake { state })
+    }
+}
+
+impl Hasher for TurboShake {
+    fn update(&mut self, input: &[u8]) {
+        self.state.update(input);
+    }
+
+    fn finalize(self, output: &mut [u8]) {
+        self.state.finalize(output);
+    }
+}
+
+impl Xof for TurboShake {
+    fn squeeze(&mut self, output: &mut [u8]) {
+        self.state.squeeze(output);
+    }
+}
@@ -0,0 +1,55 @@
+use borsh::BorshSerialize;
+use tari_tiny_keccak::{hash_borsh, Hasher, HasherWriter, Sha3};
+
+#[derive(BorshSerialize)]
+struct Block {
+    height: u64,
+    parent: [u8; 32],
+    transactions: Vec<Vec<u8>>,
+    memo: Option<String>,
+}
+
+fn block() -> Block {
+    Block {
+        height: 42,
+        parent: [7u8; 32],
+        // longer than a block of SHA3-256, so some writes span two blocks
+        transactions: (0..20u8).map(|i| vec![i; 13 * i as usize]).collect(),
+        memo: Some("hello world".into()),
+    }
+}
+
+#[test]
+fn hash_borsh_matches_encoding() {
+    let block = block();
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(&borsh::to_vec(&block).unwrap());
+    sha3.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    hash_borsh(&mut sha3, &block).unwrap();
+    sha3.finalize(&mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn hasher_writer_streams_values() {
+    let block = block();
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"prefix");
+    sha3.update(&borsh::to_vec(&block).unwrap());
+    sha3.update(&borsh::to_vec(&block.height).unwrap());
+    sha3.finalize(&mut expected);
+
+    let mut output = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"prefix");
+    let mut writer = HasherWriter::new(&mut sha3);
+    block.serialize(&mut writer).unwrap();
+    block.height.serialize(&mut writer).unwrap();
+    sha3.finalize(&mut output);
+    assert_eq!(expected, output);
+}
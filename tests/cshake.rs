@@ -1,4 +1,5 @@
-use tiny_keccak::{CShake, Hasher, Xof};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tari_tiny_keccak::{CShake, Error, Hasher, KeccakF, Xof};
 
 #[test]
 fn test_cshake128_one() {
@@ -114,3 +115,92 @@ fn test_cshake_as_shake() {
 
     assert_eq!(expected, &output);
 }
+
+#[test]
+fn test_cshake_encoded_name_fills_block() {
+    // left_encode(136) || encode_string("KMAC") || encode_string(custom) is exactly 136 bytes.
+    // cSHAKE256 of bytepad(encode_string(key), 136) || data || right_encode(256) with the name
+    // "KMAC" is KMAC256(key, data, 256, custom), so the expected output is the one of
+    // `openssl mac -macopt hexkey:404142..5f -macopt hexcustom:5353..53 -macopt size:32 KMAC256`
+    // (OpenSSL 3).
+    let key: Vec<u8> = (0x40..0x60).collect();
+    let custom = [0x53u8; 125];
+    let mut input = vec![1, 136, 2, 1, 0];
+    input.extend_from_slice(&key);
+    input.resize(136, 0);
+    input.extend_from_slice(b"\x00\x01\x02\x03");
+    input.extend_from_slice(&[1, 0, 2]);
+    let expected = b"\
+        \x6C\x2A\xD5\x10\x8C\x4D\x5E\x07\x5D\x20\x5B\x97\x0E\xD9\x69\xA6\
+        \x25\x77\x90\x96\x89\xA7\xB3\x22\x15\xB2\xEF\x9F\x10\x35\x4A\x04\
+    ";
+    let mut output = [0u8; 32];
+    let mut cshake = CShake::v256(b"KMAC", &custom);
+    cshake.update(&input);
+    cshake.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_cshake_to_bytes_resumes() {
+    for (name, custom) in [(&b""[..], &b""[..]), (&b"N"[..], &b"C"[..])].iter() {
+        let mut cshake = CShake::v128(name, custom);
+        cshake.update(&[0x5a; 500]);
+        let mut restored: CShake = CShake::from_bytes(&cshake.to_bytes()).unwrap();
+
+        cshake.update(b"abc");
+        restored.update(b"abc");
+        let mut output = [0u8; 32];
+        let mut restored_output = [0u8; 32];
+        cshake.squeeze(&mut output);
+        restored.squeeze(&mut restored_output);
+        assert_eq!(output, restored_output);
+    }
+}
+
+#[test]
+fn test_cshake_try_new() {
+    assert!(CShake::<KeccakF>::try_new(b"", b"", 128).is_ok());
+    assert_eq!(
+        CShake::<KeccakF>::try_new(b"name", b"", 512).err(),
+        Some(Error::InvalidSecurityLevel(512))
+    );
+}
+
+#[test]
+fn test_cshake_save() {
+    for (name, custom) in [(&b""[..], &b""[..]), (&b"name"[..], &b"custom"[..])].iter() {
+        let mut cshake = CShake::v256(name, custom);
+        let mut out = [0u8; 64];
+        cshake.update(b"hello");
+        let mut buf = Vec::new();
+        cshake.serialize(&mut buf).unwrap();
+        cshake.update(b" world");
+        cshake.finalize(&mut out);
+
+        let buf = &mut buf.as_slice();
+        let mut cshake = CShake::deserialize(buf).unwrap();
+        cshake.update(b" world");
+        let mut out2 = [0u8; 64];
+        cshake.finalize(&mut out2);
+
+        assert_eq!(&out[..], &out2[..]);
+    }
+}
+
+#[test]
+fn test_cshake_save_squeezing() {
+    let mut cshake = CShake::v128(b"name", b"custom");
+    let mut out = [0u8; 400];
+    cshake.update(b"hello world");
+    cshake.squeeze(&mut out[..200]);
+    let buf = borsh::to_vec(&cshake).unwrap();
+    assert_eq!(0x04, buf[203]);
+    cshake.squeeze(&mut out[200..]);
+
+    let mut restored = CShake::try_from_slice(&buf).unwrap();
+    let mut out2 = [0u8; 200];
+    restored.squeeze(&mut out2);
+
+    assert_eq!(&out[200..], &out2[..]);
+}
@@ -0,0 +1,195 @@
+use hmac::SimpleHmac;
+use tari_tiny_keccak::digest::{
+    const_oid::AssociatedOid, Digest, ExtendableOutput, ExtendableOutputReset, FixedOutputReset,
+    Mac, Update, XofReader,
+};
+use tari_tiny_keccak::{
+    CShake, Hasher, IntoXof, KangarooTwelve, Keccak256, Kmac128, Kmac256, ParallelHash, Sha3,
+    Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake, Shake128, Shake256, TupleHash, TurboShake, Xof,
+};
+
+fn digest<D: Digest>(input: &[u8]) -> Vec<u8> {
+    let mut hasher = D::new();
+    Digest::update(&mut hasher, &input[..3]);
+    Digest::update(&mut hasher, &input[3..]);
+    hasher.finalize().to_vec()
+}
+
+fn read_xof<X: ExtendableOutput>(mut xof: X, input: &[u8], output_len: usize) -> Vec<u8> {
+    Update::update(&mut xof, input);
+    let mut reader = xof.finalize_xof();
+    let mut output = vec![0u8; output_len];
+    let (first, second) = output.split_at_mut(output_len / 3);
+    reader.read(first);
+    reader.read(second);
+    output
+}
+
+fn hasher_output<H: Hasher>(mut hasher: H, input: &[u8], output_len: usize) -> Vec<u8> {
+    hasher.update(input);
+    let mut output = vec![0u8; output_len];
+    hasher.finalize(&mut output);
+    output
+}
+
+#[test]
+fn test_digest_sha3() {
+    let input = b"hello world";
+    let expected = b"\
+        \x64\x4b\xcc\x7e\x56\x43\x73\x04\x09\x99\xaa\xc8\x9e\x76\x22\xf3\
+        \xca\x71\xfb\xa1\xd9\x72\xfd\x94\xa3\x1c\x3b\xfb\xf2\x4e\x39\x38\
+    ";
+    assert_eq!(&digest::<Sha3_256>(input)[..], &expected[..]);
+    assert_eq!(
+        digest::<Sha3_224>(input),
+        hasher_output(Sha3::v224(), input, 28)
+    );
+    assert_eq!(
+        digest::<Sha3_384>(input),
+        hasher_output(Sha3::v384(), input, 48)
+    );
+    assert_eq!(
+        digest::<Sha3_512>(input),
+        hasher_output(Sha3::v512(), input, 64)
+    );
+}
+
+#[test]
+fn test_digest_keccak256() {
+    let expected = b"\
+        \xc5\xd2\x46\x01\x86\xf7\x23\x3c\x92\x7e\x7d\xb2\xdc\xc7\x03\xc0\
+        \xe5\x00\xb6\x53\xca\x82\x27\x3b\x7b\xfa\xd8\x04\x5d\x85\xa4\x70\
+    ";
+    assert_eq!(&Keccak256::digest(b"")[..], &expected[..]);
+}
+
+#[test]
+fn test_digest_reset() {
+    let mut sha3 = Sha3_256::new();
+    Digest::update(&mut sha3, b"discarded");
+    Digest::reset(&mut sha3);
+    Digest::update(&mut sha3, b"hello world");
+    let first = sha3.finalize_reset();
+    Digest::update(&mut sha3, b"hello world");
+    assert_eq!(first, sha3.finalize_fixed_reset());
+    assert_eq!(&first[..], &digest::<Sha3_256>(b"hello world")[..]);
+}
+
+#[test]
+fn test_digest_shake() {
+    let expected = b"\
+        \x7f\x9c\x2b\xa4\xe8\x8f\x82\x7d\x61\x60\x45\x50\x76\x05\x85\x3e\
+        \xd7\x3b\x80\x93\xf6\xef\xbc\x88\xeb\x1a\x6e\xac\xfa\x66\xef\x26\
+    ";
+    assert_eq!(&read_xof(Shake128::default(), b"", 32)[..], &expected[..]);
+    assert_eq!(
+        read_xof(Shake256::default(), b"abc", 100),
+        hasher_output(Shake::v256(), b"abc", 100)
+    );
+
+    let mut shake = Shake128::default();
+    Update::update(&mut shake, b"discarded");
+    let mut discarded = [0u8; 32];
+    shake.finalize_xof_reset().read(&mut discarded);
+    let mut output = [0u8; 32];
+    shake.finalize_xof_reset().read(&mut output);
+    assert_eq!(&output, expected);
+}
+
+#[test]
+fn test_digest_extendable_output() {
+    let input: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+    assert_eq!(
+        read_xof(CShake::v128(b"name", b"custom"), &input, 100),
+        hasher_output(CShake::v128(b"name", b"custom"), &input, 100)
+    );
+    assert_eq!(
+        read_xof(TurboShake::v256(0x1f), &input, 100),
+        hasher_output(TurboShake::v256(0x1f), &input, 100)
+    );
+
+    // the extendable-output functions of SP800-185 differ from their fixed-output versions
+    let mut tuple_hash = TupleHash::v128(b"custom");
+    Hasher::update(&mut tuple_hash, &input);
+    let mut expected = [0u8; 100];
+    tuple_hash.into_xof().squeeze(&mut expected);
+    assert_eq!(
+        &read_xof(TupleHash::v128(b"custom"), &input, 100)[..],
+        &expected[..]
+    );
+
+    let mut parallel_hash = ParallelHash::v256(b"", 64);
+    Hasher::update(&mut parallel_hash, &input);
+    let mut expected = [0u8; 100];
+    parallel_hash.into_xof().squeeze(&mut expected);
+    assert_eq!(
+        &read_xof(ParallelHash::v256(b"", 64), &input, 100)[..],
+        &expected[..]
+    );
+
+    let mut kangaroo = KangarooTwelve::new(b"");
+    Hasher::update(&mut kangaroo, &input);
+    let mut expected = [0u8; 100];
+    kangaroo.into_xof().squeeze(&mut expected);
+    assert_eq!(
+        &read_xof(KangarooTwelve::new(b""), &input, 100)[..],
+        &expected[..]
+    );
+}
+
+#[test]
+fn test_digest_kmac() {
+    // samples #1 and #5 of the NIST KMAC examples, whose customization string is empty
+    let key = b"\
+        \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4A\x4B\x4C\x4D\x4E\x4F\
+        \x50\x51\x52\x53\x54\x55\x56\x57\x58\x59\x5A\x5B\x5C\x5D\x5E\x5F\
+    ";
+    let expected = b"\
+        \xE5\x78\x0B\x0D\x3E\xA6\xF7\xD3\xA4\x29\xC5\x70\x6A\xA4\x3A\x00\
+        \xFA\xDB\xD7\xD4\x96\x28\x83\x9E\x31\x87\x24\x3F\x45\x6E\xE1\x4E\
+    ";
+    let mut kmac = <Kmac128 as Mac>::new_from_slice(key).unwrap();
+    Mac::update(&mut kmac, b"\x00\x01\x02\x03");
+    kmac.clone().verify_slice(expected).unwrap();
+    assert_eq!(&kmac.finalize_reset().into_bytes()[..], &expected[..]);
+    Mac::update(&mut kmac, b"\x00\x01\x02\x03");
+    assert_eq!(&kmac.finalize().into_bytes()[..], &expected[..]);
+
+    let data: Vec<u8> = (0..0xc8).collect();
+    let expected = b"\
+        \x75\x35\x8C\xF3\x9E\x41\x49\x4E\x94\x97\x07\x92\x7C\xEE\x0A\xF2\
+        \x0A\x3F\xF5\x53\x90\x4C\x86\xB0\x8F\x21\xCC\x41\x4B\xCF\xD6\x91\
+        \x58\x9D\x27\xCF\x5E\x15\x36\x9C\xBB\xFF\x8B\x9A\x4C\x2E\xB1\x78\
+        \x00\x85\x5D\x02\x35\xFF\x63\x5D\xA8\x25\x33\xEC\x6B\x75\x9B\x69\
+    ";
+    let mut kmac = <Kmac256 as Mac>::new_from_slice(key).unwrap();
+    Mac::update(&mut kmac, &data);
+    assert_eq!(&kmac.finalize().into_bytes()[..], &expected[..]);
+
+    // a key of any length is accepted
+    assert!(<Kmac128 as Mac>::new_from_slice(b"").is_ok());
+}
+
+#[test]
+fn test_digest_hmac_sha3() {
+    // computed with the HMAC and SHA3-256 of OpenSSL, through Python's hmac and hashlib
+    let expected = b"\
+        \x8c\x6e\x06\x83\x40\x94\x27\xf8\x93\x17\x11\xb1\x0c\xa9\x2a\x50\
+        \x6e\xb1\xfa\xfa\x48\xfa\xdd\x66\xd7\x61\x26\xf4\x7a\xc2\xc3\x33\
+    ";
+    let mut hmac = <SimpleHmac<Sha3_256> as Mac>::new_from_slice(b"key").unwrap();
+    Mac::update(&mut hmac, b"The quick brown fox jumps over the lazy dog");
+    assert_eq!(&hmac.finalize().into_bytes()[..], &expected[..]);
+}
+
+#[test]
+fn test_digest_oids() {
+    assert_eq!(Sha3_224::OID.to_string(), "2.16.840.1.101.3.4.2.7");
+    assert_eq!(Sha3_256::OID.to_string(), "2.16.840.1.101.3.4.2.8");
+    assert_eq!(Sha3_384::OID.to_string(), "2.16.840.1.101.3.4.2.9");
+    assert_eq!(Sha3_512::OID.to_string(), "2.16.840.1.101.3.4.2.10");
+    assert_eq!(Shake128::OID.to_string(), "2.16.840.1.101.3.4.2.11");
+    assert_eq!(Shake256::OID.to_string(), "2.16.840.1.101.3.4.2.12");
+    assert_eq!(Kmac128::OID.to_string(), "2.16.840.1.101.3.4.2.19");
+    assert_eq!(Kmac256::OID.to_string(), "2.16.840.1.101.3.4.2.20");
+}
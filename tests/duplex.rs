@@ -0,0 +1,235 @@
+use borsh::BorshDeserialize;
+use tari_tiny_keccak::{Duplex, Error, Hasher, KeccakF, Mode, Sponge, Xof};
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|j| (j % 251) as u8).collect()
+}
+
+/// Runs the sequence of calls the expected outputs were computed with, and returns the outputs
+/// of the calls that have one.
+///
+/// The expected outputs were computed with the `f1600` function of the RustCrypto `keccak` crate,
+/// version 0.1.6, which shares no code with this crate. Every call pads its input to a block with
+/// `pad10*1`, adds it to the outer part of the state, or overwrites the outer part with it in
+/// overwrite mode, permutes the state and reads the output from the start of the state. That
+/// `f1600` itself gives the SHA3-256 of `abc` of FIPS 202 when used in a sponge.
+fn duplex_outputs(mut duplex: Duplex, overwrite: bool) -> Vec<Vec<u8>> {
+    let rate = duplex.rate();
+    let calls = [
+        (Vec::new(), 32),
+        (b"abc".to_vec(), 32),
+        (pattern(rate - 1), 16),
+        (pattern(7), 0),
+        (Vec::new(), 32),
+    ];
+    let mut outputs = Vec::new();
+    for (input, output_len) in calls.iter() {
+        let mut output = vec![0u8; *output_len];
+        if overwrite {
+            duplex.duplexing_overwrite(input, &mut output);
+        } else {
+            duplex.duplexing(input, &mut output);
+        }
+        if !output.is_empty() {
+            outputs.push(output);
+        }
+    }
+    outputs
+}
+
+fn test_duplex(duplex: Duplex, overwrite: bool, expected: [&[u8]; 4]) {
+    let outputs = duplex_outputs(duplex, overwrite);
+    assert_eq!(expected.len(), outputs.len());
+    for (output, expected) in outputs.iter().zip(expected.iter()) {
+        assert_eq!(&output[..], *expected);
+    }
+}
+
+#[test]
+fn test_duplex_v128() {
+    let expected: [&[u8]; 4] = [
+        b"\
+            \xbc\xf5\x6a\xc8\x82\xad\x98\x1c\xd0\xfa\x74\xf0\xf3\x97\x57\x2c\
+            \x28\x80\x1c\x1e\xb3\x1c\x1b\xac\x4c\xa7\x03\xd6\xf1\x9e\x94\x19\
+        ",
+        b"\
+            \xbd\x79\xea\x64\x76\x77\x25\x3d\xee\x32\x25\xcd\x87\xdd\xab\x11\
+            \xaf\xc7\xd5\xbb\xf8\x21\x99\xd1\x6d\x2f\x08\x76\xaa\x8e\x10\x37\
+        ",
+        b"\
+            \xea\xa9\xae\x6e\x60\xae\x5a\xea\xc4\xd7\x02\xae\x2d\x35\xc4\x33\
+        ",
+        b"\
+            \x24\xea\x2d\x1b\xab\x88\x1e\x68\x4b\x8f\x81\x05\x93\x70\x9b\x76\
+            \xcd\xc6\xe3\x3f\x22\x2d\xc4\x12\x8a\x38\x79\x85\x5d\xcc\xf2\x71\
+        ",
+    ];
+    test_duplex(Duplex::v128(), false, expected);
+}
+
+#[test]
+fn test_duplex_v256() {
+    let expected: [&[u8]; 4] = [
+        b"\
+            \xc5\xd2\x46\x01\x86\xf7\x23\x3c\x92\x7e\x7d\xb2\xdc\xc7\x03\xc0\
+            \xe5\x00\xb6\x53\xca\x82\x27\x3b\x7b\xfa\xd8\x04\x5d\x85\xa4\x70\
+        ",
+        b"\
+            \x31\x55\x50\xf5\x16\x6e\xd7\x9f\x16\x99\x47\x41\x37\x57\xf3\x03\
+            \xf4\xfe\x01\x42\x93\x98\x44\xce\xb4\x19\x26\xa6\x8e\xde\x0f\xd3\
+        ",
+        b"\
+            \x3a\x3a\xd6\x43\xb3\x1d\xf4\x4c\x8b\xee\xc3\x23\x49\x34\x02\x4f\
+        ",
+        b"\
+            \x7c\xad\xdc\xaf\x9a\x68\xb5\x51\xe7\x82\xbe\x5e\x09\x6b\x93\xe3\
+            \xeb\x18\x7f\x6b\x59\x8f\x49\x6c\x49\xad\x29\x60\x05\x63\xb7\x4d\
+        ",
+    ];
+    test_duplex(Duplex::v256(), false, expected);
+}
+
+#[test]
+fn test_duplex_overwrite_v128() {
+    let expected: [&[u8]; 4] = [
+        b"\
+            \xbc\xf5\x6a\xc8\x82\xad\x98\x1c\xd0\xfa\x74\xf0\xf3\x97\x57\x2c\
+            \x28\x80\x1c\x1e\xb3\x1c\x1b\xac\x4c\xa7\x03\xd6\xf1\x9e\x94\x19\
+        ",
+        b"\
+            \x5e\x42\x8f\x73\x2b\x44\x3e\x76\x93\xd7\x81\xee\xc7\x8a\x66\x3d\
+            \xae\x47\xe0\x3b\x53\xef\x73\xc6\x46\xf9\xae\x97\x36\xcc\x52\xb1\
+        ",
+        b"\
+            \x18\xe6\x61\xb9\x47\xc1\x4c\x5d\x65\x29\x90\x6d\xe3\x7e\x6b\x5c\
+        ",
+        b"\
+            \x65\x6f\x6b\x2f\x38\x97\x92\x60\x3f\x77\xa2\xe0\x97\x24\xf7\x39\
+            \xee\x76\x84\x23\x20\x66\x97\x52\x70\x83\x51\x20\x51\x12\x7e\xbe\
+        ",
+    ];
+    test_duplex(Duplex::v128(), true, expected);
+}
+
+#[test]
+fn test_duplex_first_call_is_keccak() {
+    // a duplex object that has not been called yet gives the legacy Keccak of its first input,
+    // here the empty message of ShortMsgKAT_256.txt and ShortMsgKAT_512.txt of the Keccak team's
+    // SHA-3 submission
+    let expected256 = b"\
+        \xc5\xd2\x46\x01\x86\xf7\x23\x3c\x92\x7e\x7d\xb2\xdc\xc7\x03\xc0\
+        \xe5\x00\xb6\x53\xca\x82\x27\x3b\x7b\xfa\xd8\x04\x5d\x85\xa4\x70\
+    ";
+    let mut output = [0u8; 32];
+    Duplex::v256().duplexing(b"", &mut output);
+    assert_eq!(expected256, &output);
+
+    let expected512 = b"\
+        \x0e\xab\x42\xde\x4c\x3c\xeb\x92\x35\xfc\x91\xac\xff\xe7\x46\xb2\
+        \x9c\x29\xa8\xc3\x66\xb7\xc6\x0e\x4e\x67\xc4\x66\xf3\x6a\x43\x04\
+        \xc0\x0f\xa9\xca\xf9\xd8\x79\x76\xba\x46\x9b\xcb\xe0\x67\x13\xb4\
+        \x35\xf0\x91\xef\x27\x69\xfb\x16\x0c\xda\xb3\x3d\x36\x70\x68\x0e\
+    ";
+    let mut output = [0u8; 64];
+    Duplex::<KeccakF>::new(1024).duplexing(b"", &mut output);
+    assert_eq!(&expected512[..], &output[..]);
+}
+
+#[test]
+fn test_duplex_matches_sponge() {
+    // the output of a call is the output of a sponge fed with the padded previous inputs and the
+    // current input
+    let inputs = [
+        pattern(0),
+        pattern(100),
+        pattern(135),
+        pattern(1),
+        pattern(42),
+    ];
+    let mut duplex = Duplex::v256();
+    let rate = duplex.rate();
+    let mut padded = Vec::new();
+    for input in inputs.iter() {
+        let mut output = vec![0u8; rate];
+        duplex.duplexing(input, &mut output);
+
+        let mut sponge = Sponge::builder().capacity(512).build();
+        sponge.update(&padded);
+        sponge.update(input);
+        let mut expected = vec![0u8; rate];
+        sponge.squeeze(&mut expected);
+        assert_eq!(expected, output);
+
+        let mut block = input.clone();
+        block.resize(rate, 0);
+        block[input.len()] ^= 0x01;
+        block[rate - 1] ^= 0x80;
+        padded.extend_from_slice(&block);
+    }
+}
+
+#[test]
+#[should_panic(expected = "input must be shorter than the rate")]
+fn test_duplex_rejects_full_rate_input() {
+    let mut duplex = Duplex::v128();
+    let input = pattern(duplex.rate());
+    duplex.duplexing(&input, &mut []);
+}
+
+#[test]
+#[should_panic(expected = "output cannot be longer than the rate")]
+fn test_duplex_rejects_long_output() {
+    let mut duplex = Duplex::v128();
+    let mut output = vec![0u8; duplex.rate() + 1];
+    duplex.duplexing(b"", &mut output);
+}
+
+#[test]
+fn test_duplex_try_new() {
+    assert!(Duplex::<KeccakF>::try_new(256).is_ok());
+    assert_eq!(
+        Duplex::<KeccakF>::try_new(0).err().unwrap(),
+        Error::RateOutOfRange
+    );
+}
+
+#[cfg(feature = "reduced_width")]
+#[test]
+fn test_duplex_rate_smaller_than_permutation_width() {
+    use tari_tiny_keccak::KeccakF200;
+
+    assert_eq!(
+        Duplex::<KeccakF200>::try_new(256).err().unwrap(),
+        Error::RateOutOfRange
+    );
+    assert_eq!(18, Duplex::<KeccakF200>::new(56).rate());
+}
+
+#[test]
+fn test_duplex_checkpoint() {
+    let mut duplex = Duplex::v256();
+    let mut output = [0u8; 32];
+    duplex.duplexing(b"key", &mut []);
+    let buf = borsh::to_vec(&duplex).unwrap();
+    let mut restored = Duplex::try_from_slice(&buf).unwrap();
+    assert_eq!(restored.rate(), duplex.rate());
+
+    let mut output2 = [0u8; 32];
+    duplex.duplexing(b"message", &mut output);
+    restored.duplexing(b"message", &mut output2);
+    assert_eq!(output, output2);
+}
+
+#[test]
+fn test_duplex_checkpoint_rejected() {
+    let buf = borsh::to_vec(&Duplex::v128()).unwrap();
+    let err = |buf: &[u8]| Duplex::try_from_slice(buf).err().unwrap().to_string();
+
+    // the lanes are followed by the offset, the rate and the mode
+    let mut offset = buf.clone();
+    offset[200] = 1;
+    assert_eq!(err(&offset), Error::InvalidState.to_string());
+    let mut squeezing = buf.clone();
+    squeezing[202] = Mode::Squeezing as u8;
+    assert_eq!(err(&squeezing), Error::InvalidState.to_string());
+}
@@ -1,4 +1,8 @@
-use tiny_keccak::{Hasher, KangarooTwelve};
+use borsh::{io, BorshDeserialize};
+use tari_tiny_keccak::{
+    Error, Hasher, IntoXof, KangarooTwelve, KangarooTwelve256, KangarooTwelveXof,
+    MarsupilamiFourteen, MarsupilamiFourteenXof, Mode, Xof,
+};
 
 fn pattern(len: usize) -> Vec<u8> {
     (0..len).map(|j| (j % 251) as u8).collect()
@@ -17,6 +21,37 @@ fn test_kangaroo_twelve<A: AsRef<[u8]>, B: AsRef<[u8]>>(
     assert_eq!(&res[output_len - expected.len()..], expected);
 }
 
+fn test_kangaroo_twelve256<A: AsRef<[u8]>, B: AsRef<[u8]>>(
+    custom_string: A,
+    message: B,
+    output_len: usize,
+    expected: &[u8],
+) {
+    let mut kangaroo = KangarooTwelve256::new(custom_string.as_ref());
+    kangaroo.update(message.as_ref());
+    let mut res = vec![0; output_len];
+    kangaroo.finalize(&mut res);
+    assert_eq!(&res[output_len - expected.len()..], expected);
+}
+
+// The MarsupilamiFourteen vectors use the messages, customization strings and output lengths of
+// the KT256 cases of draft-irtf-cfrg-kangarootwelve. They were computed with a Python model of
+// the tree of the KangarooTwelve paper (https://eprint.iacr.org/2016/770) on
+// `keccak-p[1600, 14]`. The same model, with 12 rounds, reproduces the KT128 and KT256 vectors
+// of the draft.
+fn test_marsupilami_fourteen<A: AsRef<[u8]>, B: AsRef<[u8]>>(
+    custom_string: A,
+    message: B,
+    output_len: usize,
+    expected: &[u8],
+) {
+    let mut marsupilami = MarsupilamiFourteen::new(custom_string.as_ref());
+    marsupilami.update(message.as_ref());
+    let mut res = vec![0; output_len];
+    marsupilami.finalize(&mut res);
+    assert_eq!(&res[output_len - expected.len()..], expected);
+}
+
 #[test]
 fn empty_kangaroo_twelve() {
     let expected = b"\
@@ -68,7 +103,7 @@ fn kangaroo_twelve_with_custom_string_and_message() {
         \xd8\x48\xc5\x06\x8c\xed\x73\x6f\x44\x62\x15\x9b\x98\x67\xfd\x4c\
         \x20\xb8\x08\xac\xc3\xd5\xbc\x48\xe0\xb0\x6b\xa0\xa3\x76\x2e\xc4\
     ";
-    test_kangaroo_twelve(pattern(41), &[0xff], 32, expected);
+    test_kangaroo_twelve(pattern(41), [0xff], 32, expected);
 }
 
 #[test]
@@ -79,8 +114,345 @@ fn kangaroo_twelve_with_custom_string_and_message2() {
     ";
     test_kangaroo_twelve(
         pattern(68921),
-        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
         32,
         expected,
     );
 }
+
+#[test]
+fn kangaroo_twelve256_empty() {
+    let expected = b"\
+        \xb2\x3d\x2e\x9c\xea\x9f\x49\x04\xe0\x2b\xec\x06\x81\x7f\xc1\x0c\
+        \xe3\x8c\xe8\xe9\x3e\xf4\xc8\x9e\x65\x37\x07\x6a\xf8\x64\x64\x04\
+        \xe3\xe8\xb6\x81\x07\xb8\x83\x3a\x5d\x30\x49\x0a\xa3\x34\x82\x35\
+        \x3f\xd4\xad\xc7\x14\x8e\xcb\x78\x28\x55\x00\x3a\xae\xbd\xe4\xa9\
+    ";
+    test_kangaroo_twelve256("", "", 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_long() {
+    let expected = b"\
+        \xb0\x92\x53\x19\xd8\xea\x1e\x12\x1a\x60\x98\x21\xec\x19\xef\xea\
+        \x89\xe6\xd0\x8d\xae\xe1\x66\x2b\x69\xc8\x40\x28\x9f\x18\x8b\xa8\
+        \x60\xf5\x57\x60\xb6\x1f\x82\x11\x4c\x03\x0c\x97\xe5\x17\x84\x49\
+        \x60\x8c\xcd\x2c\xd2\xd9\x19\xfc\x78\x29\xff\x69\x93\x1a\xc4\xd0\
+    ";
+    test_kangaroo_twelve256("", "", 128, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_very_long() {
+    let expected = b"\
+        \xad\x4a\x1d\x71\x8c\xf9\x50\x50\x67\x09\xa4\xc3\x33\x96\x13\x9b\
+        \x44\x49\x04\x1f\xc7\x9a\x05\xd6\x8d\xa3\x5f\x1e\x45\x35\x22\xe0\
+        \x56\xc6\x4f\xe9\x49\x58\xe7\x08\x5f\x29\x64\x88\x82\x59\xb9\x93\
+        \x27\x52\xf3\xcc\xd8\x55\x28\x8e\xfe\xe5\xfc\xbb\x8b\x56\x30\x69\
+    ";
+    test_kangaroo_twelve256("", "", 10064, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_message() {
+    let expected = b"\
+        \x0d\x00\x5a\x19\x40\x85\x36\x02\x17\x12\x8c\xf1\x7f\x91\xe1\xf7\
+        \x13\x14\xef\xa5\x56\x45\x39\xd4\x44\x91\x2e\x34\x37\xef\xa1\x7f\
+        \x82\xdb\x6f\x6f\xfe\x76\xe7\x81\xea\xa0\x68\xbc\xe0\x1f\x2b\xbf\
+        \x81\xea\xcb\x98\x3d\x72\x30\xf2\xfb\x02\x83\x4a\x21\xb1\xdd\xd0\
+    ";
+    test_kangaroo_twelve256("", pattern(1), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_message2() {
+    let expected = b"\
+        \x1b\xa3\xc0\x2b\x1f\xc5\x14\x47\x4f\x06\xc8\x97\x99\x78\xa9\x05\
+        \x6c\x84\x83\xf4\xa1\xb6\x3d\x0d\xcc\xef\xe3\xa2\x8a\x2f\x32\x3e\
+        \x1c\xdc\xca\x40\xeb\xf0\x06\xac\x76\xef\x03\x97\x15\x23\x46\x83\
+        \x7b\x12\x77\xd3\xe7\xfa\xa9\xc9\x65\x3b\x19\x07\x50\x98\x52\x7b\
+    ";
+    test_kangaroo_twelve256("", pattern(17), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_message3() {
+    let expected = b"\
+        \xde\x8c\xcb\xc6\x3e\x0f\x13\x3e\xbb\x44\x16\x81\x4d\x4c\x66\xf6\
+        \x91\xbb\xf8\xb6\xa6\x1e\xc0\xa7\x70\x0f\x83\x6b\x08\x6c\xb0\x29\
+        \xd5\x4f\x12\xac\x71\x59\x47\x2c\x72\xdb\x11\x8c\x35\xb4\xe6\xaa\
+        \x21\x3c\x65\x62\xca\xaa\x9d\xcc\x51\x89\x59\xe6\x9b\x10\xf3\xba\
+    ";
+    test_kangaroo_twelve256("", pattern(289), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_message4() {
+    let expected = b"\
+        \x64\x7e\xfb\x49\xfe\x9d\x71\x75\x00\x17\x1b\x41\xe7\xf1\x1b\xd4\
+        \x91\x54\x44\x43\x20\x99\x97\xce\x1c\x25\x30\xd1\x5e\xb1\xff\xbb\
+        \x59\x89\x35\xef\x95\x45\x28\xff\xc1\x52\xb1\xe4\xd7\x31\xee\x26\
+        \x83\x68\x06\x74\x36\x5c\xd1\x91\xd5\x62\xba\xe7\x53\xb8\x4a\xa5\
+    ";
+    test_kangaroo_twelve256("", pattern(4913), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_message5() {
+    let expected = b"\
+        \xb0\x62\x75\xd2\x84\xcd\x1c\xf2\x05\xbc\xbe\x57\xdc\xcd\x3e\xc1\
+        \xff\x66\x86\xe3\xed\x15\x77\x63\x83\xe1\xf2\xfa\x3c\x6a\xc8\xf0\
+        \x8b\xf8\xa1\x62\x82\x9d\xb1\xa4\x4b\x2a\x43\xff\x83\xdd\x89\xc3\
+        \xcf\x1c\xeb\x61\xed\xe6\x59\x76\x6d\x5c\xcf\x81\x7a\x62\xba\x8d\
+    ";
+    test_kangaroo_twelve256("", pattern(83521), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_custom_string() {
+    let expected = b"\
+        \x92\x80\xf5\xcc\x39\xb5\x4a\x5a\x59\x4e\xc6\x3d\xe0\xbb\x99\x37\
+        \x1e\x46\x09\xd4\x4b\xf8\x45\xc2\xf5\xb8\xc3\x16\xd7\x2b\x15\x98\
+        \x11\xf7\x48\xf2\x3e\x3f\xab\xbe\x5c\x32\x26\xec\x96\xc6\x21\x86\
+        \xdf\x2d\x33\xe9\xdf\x74\xc5\x06\x9c\xee\xcb\xb4\xdd\x10\xef\xf6\
+    ";
+    test_kangaroo_twelve256(pattern(1), "", 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_custom_string_and_message() {
+    let expected = b"\
+        \x47\xef\x96\xdd\x61\x6f\x20\x09\x37\xaa\x78\x47\xe3\x4e\xc2\xfe\
+        \xae\x80\x87\xe3\x76\x1d\xc0\xf8\xc1\xa1\x54\xf5\x1d\xc9\xcc\xf8\
+        \x45\xd7\xad\xbc\xe5\x7f\xf6\x4b\x63\x97\x22\xc6\xa1\x67\x2e\x3b\
+        \xf5\x37\x2d\x87\xe0\x0a\xff\x89\xbe\x97\x24\x07\x56\x99\x88\x53\
+    ";
+    test_kangaroo_twelve256(pattern(41), [0xff], 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_custom_string_and_message2() {
+    let expected = b"\
+        \x3b\x48\x66\x7a\x50\x51\xc5\x96\x6c\x53\xc5\xd4\x2b\x95\xde\x45\
+        \x1e\x05\x58\x4e\x78\x06\xe2\xfb\x76\x5e\xda\x95\x90\x74\x17\x2c\
+        \xb4\x38\xa9\xe9\x1d\xde\x33\x7c\x98\xe9\xc4\x1b\xed\x94\xc4\xe0\
+        \xae\xf4\x31\xd0\xb6\x4e\xf2\x32\x4f\x79\x32\xca\xa6\xf5\x49\x69\
+    ";
+    test_kangaroo_twelve256(pattern(1681), [0xff, 0xff, 0xff], 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_with_custom_string_and_message3() {
+    let expected = b"\
+        \xe0\x91\x1c\xc0\x00\x25\xe1\x54\x08\x31\xe2\x66\xd9\x4a\xdd\x9b\
+        \x98\x71\x21\x42\xb8\x0d\x26\x29\xe6\x43\xaa\xc4\xef\xaf\x5a\x3a\
+        \x30\xa8\x8c\xbf\x4a\xc2\xa9\x1a\x24\x32\x74\x30\x54\xfb\xcc\x98\
+        \x97\x67\x0e\x86\xba\x8c\xec\x2f\xc2\xac\xe9\xc9\x66\x36\x97\x24\
+    ";
+    test_kangaroo_twelve256(
+        pattern(68921),
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        64,
+        expected,
+    );
+}
+
+#[test]
+fn kangaroo_twelve256_chunk_boundary() {
+    let expected = b"\
+        \x2c\xdc\x6f\xc6\x19\x11\x25\x56\x21\x2f\x83\xa9\x9a\x57\xc9\xc5\
+        \xd8\x6b\x8d\xba\x2b\xa0\xe7\x3f\xab\x81\x3e\x93\x2d\xb8\xe4\xe6\
+        \x5d\xbe\x0a\x24\x11\x96\xd3\x4a\xe8\x59\xe8\x17\x2e\x37\x7d\xec\
+        \xe2\x04\xaa\x3c\x74\xbe\xfe\xb0\x04\x99\x0b\x45\xd9\x7e\xb1\xc1\
+    ";
+    test_kangaroo_twelve256(pattern(8189), pattern(8191), 64, expected);
+}
+
+#[test]
+fn kangaroo_twelve256_chunk_boundary2() {
+    let expected = b"\
+        \xf4\xb5\x90\x8b\x92\x9f\xfe\x01\xe0\xf7\x9e\xc2\xf2\x12\x43\xd4\
+        \x1a\x39\x6b\x2e\x73\x03\xa6\xaf\x1d\x63\x99\xcd\x6c\x7a\x0a\x2d\
+        \xd7\xc4\xf6\x07\xe8\x27\x7f\x9c\x9b\x1c\xb4\xab\x9d\xdc\x59\xd4\
+        \xb9\x2d\x1f\xc7\x55\x84\x41\xf1\x83\x2c\x32\x79\xa4\x24\x1b\x8b\
+    ";
+    test_kangaroo_twelve256(pattern(8190), pattern(8192), 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_empty() {
+    let expected = b"\
+        \x6f\x66\xef\x14\x74\xeb\x53\x80\x7a\xa3\x29\x25\x7c\x76\x8b\xb8\
+        \x88\x93\xd9\xf0\x86\xe5\x1d\xa2\xf5\xc8\x0d\x17\xca\x0f\xc5\x7d\
+        \x5a\x24\xfa\xc8\x79\x01\x4f\x8b\x30\xa3\xfd\xf5\xac\x56\xeb\xaf\
+        \xa2\x19\xeb\x89\x1d\x4b\xbb\xab\x7e\x1d\xf3\xb2\x72\x05\xb4\x59\
+    ";
+    test_marsupilami_fourteen("", "", 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_long() {
+    let expected = b"\
+        \xc0\x93\x22\xde\x15\x13\xd0\xcd\x60\x47\x28\xf3\x6d\x11\xad\xff\
+        \x58\xb9\x3f\x77\x63\x81\x09\x5a\x07\x19\x21\xea\xfb\x30\xe1\xe3\
+        \xa9\x87\xea\x7b\xb4\x13\xf5\xf2\xba\xe0\x40\x89\xa8\x66\xb4\x79\
+        \xd2\x89\x3a\x11\xb3\x29\xa1\x65\x7f\xbe\x3c\xaa\xb8\x07\x78\x68\
+    ";
+    test_marsupilami_fourteen("", "", 10064, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_with_message() {
+    let expected = b"\
+        \xaa\x76\x4f\xd8\xb3\x8f\x19\x97\x6a\x30\x5c\xb0\x07\xf1\x93\x84\
+        \xb2\x10\xa5\xc7\xb0\xfc\x44\x99\xd6\xf8\x3c\x62\x27\xbf\xf8\x50\
+        \x27\x0b\x88\x0c\xff\x3f\x17\x32\x5b\x84\x3e\x97\x2a\xe0\xb9\x9a\
+        \x25\xfa\x0e\x00\x50\xcc\x74\x8f\x37\xc4\xcf\xc2\x59\x2f\xd1\x72\
+    ";
+    test_marsupilami_fourteen("", pattern(17), 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_with_message2() {
+    let expected = b"\
+        \x0a\xc8\x9b\x11\xa0\x6f\x46\xb2\xf6\xfe\xef\xf0\x46\xc9\x7e\x90\
+        \xdc\x02\x91\x0a\xe5\x09\xb8\x73\x9c\xfe\xa5\xdf\x1d\xf9\x0b\x82\
+        \x89\x5a\x5f\xad\x67\xad\x2f\xa4\x12\x59\x09\x07\x56\xc0\xd9\x88\
+        \x44\x0f\xa3\x26\x7a\x48\x38\x0a\xda\x5d\xf9\xc7\xf0\x29\x07\x57\
+    ";
+    test_marsupilami_fourteen("", pattern(4913), 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_with_custom_string() {
+    let expected = b"\
+        \xe6\xc2\x3c\xee\xab\x20\x89\xd1\x4d\xc3\xb0\x88\xfd\xfe\x6d\x44\
+        \x18\xbf\x8a\x6f\x33\x0f\xb3\xed\xcc\x30\x0c\xd8\x1e\x1b\xef\x2f\
+        \x0c\xab\x47\x9b\x19\x6e\x53\xbe\x8f\xa2\x87\x85\x4d\x48\x4f\xdf\
+        \xd0\x84\xaf\x3a\xe1\xff\xac\x9b\x04\xc2\xe9\xea\x2b\x5a\x1c\x7b\
+    ";
+    test_marsupilami_fourteen(pattern(1), "", 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_with_custom_string_and_message() {
+    let expected = b"\
+        \x2b\xab\x75\xb3\x1b\x8c\x30\x49\xab\xeb\x76\x74\x77\x47\x71\xb6\
+        \x4f\x59\x22\x5b\xe2\x0e\x93\x0e\xbd\xbf\x8e\x37\xc2\x4f\xad\x69\
+        \xbe\xf4\x7a\x41\x2d\xb6\x20\x94\xd5\xcc\x95\xde\x8e\x4f\xc2\xc0\
+        \xae\x65\xfd\x0f\x4d\x03\xbb\x56\xe6\x29\x2b\xe0\x84\xfc\xc8\xe3\
+    ";
+    test_marsupilami_fourteen(pattern(41), [0xff], 64, expected);
+}
+
+#[test]
+fn marsupilami_fourteen_with_custom_string_and_message2() {
+    let expected = b"\
+        \x61\x58\x3c\xdf\xaa\x64\xab\x60\xe7\x7b\x8c\x8b\xdd\x0a\xd0\x88\
+        \xf9\xd7\x60\xb2\x94\x4f\x7d\x64\xc5\xdd\x81\xce\x7e\x92\xd9\x6b\
+        \xff\x67\x84\x3a\x1e\xed\x51\xf3\x01\xdb\x51\xff\x54\xfd\xcd\x44\
+        \x62\xfd\x05\x14\x25\xd4\xc2\xed\xba\x74\xac\x2b\x15\x32\xec\x14\
+    ";
+    test_marsupilami_fourteen(
+        pattern(68921),
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        64,
+        expected,
+    );
+}
+
+#[test]
+fn kangaroo_twelve_checkpoint() {
+    // splits in the first chunk, on and after chunk boundaries, and before and after four
+    // whole chunks
+    let input = pattern(6 * 8192 + 100);
+    for split in [0, 100, 8192, 8193, 2 * 8192 + 5, 5 * 8192, 6 * 8192 + 50].iter() {
+        let mut kangaroo = KangarooTwelve::new(&b"custom"[..]);
+        kangaroo.update(&input[..*split]);
+        let buf = borsh::to_vec(&kangaroo).unwrap();
+        kangaroo.update(&input[*split..]);
+        let mut output = [0u8; 32];
+        kangaroo.finalize(&mut output);
+
+        let mut restored = KangarooTwelve::<Vec<u8>>::try_from_slice(&buf).unwrap();
+        restored.update(&input[*split..]);
+        let mut output2 = [0u8; 32];
+        restored.finalize(&mut output2);
+        assert_eq!(output, output2);
+    }
+}
+
+#[test]
+fn marsupilami_fourteen_checkpoint() {
+    let input = pattern(3 * 8192);
+    let mut hasher = MarsupilamiFourteen::new(b"");
+    hasher.update(&input[..10000]);
+    let buf = borsh::to_vec(&hasher).unwrap();
+    hasher.update(&input[10000..]);
+    let mut xof = hasher.into_xof();
+    let mut output = [0u8; 400];
+    xof.squeeze(&mut output[..200]);
+    let xof_buf = borsh::to_vec(&xof).unwrap();
+    xof.squeeze(&mut output[200..]);
+
+    let mut restored = MarsupilamiFourteen::<Vec<u8>>::try_from_slice(&buf).unwrap();
+    restored.update(&input[10000..]);
+    let mut output2 = [0u8; 400];
+    restored.into_xof().squeeze(&mut output2);
+    assert_eq!(&output[..], &output2[..]);
+
+    let mut restored = MarsupilamiFourteenXof::try_from_slice(&xof_buf).unwrap();
+    restored.squeeze(&mut output2[..200]);
+    assert_eq!(&output[200..], &output2[..200]);
+}
+
+#[test]
+fn kangaroo_twelve_checkpoint_rejected() {
+    let mut kangaroo = KangarooTwelve::new(b"");
+    kangaroo.update(&pattern(10000));
+    let buf = borsh::to_vec(&kangaroo).unwrap();
+    let mismatch = Error::AlgorithmMismatch.to_string();
+
+    let err = KangarooTwelve256::<Vec<u8>>::try_from_slice(&buf).err();
+    assert_eq!(err.unwrap().to_string(), mismatch);
+    let err = MarsupilamiFourteen::<Vec<u8>>::try_from_slice(&buf).err();
+    assert_eq!(err.unwrap().to_string(), mismatch);
+    assert!(KangarooTwelveXof::try_from_slice(&buf).is_err());
+
+    let mut level = buf.clone();
+    level[2..4].copy_from_slice(&256u16.to_le_bytes());
+    let err = KangarooTwelve::<Vec<u8>>::try_from_slice(&level).err();
+    assert_eq!(err.unwrap().to_string(), mismatch);
+    let mut version = buf.clone();
+    version[0] = 0;
+    let err = KangarooTwelve::<Vec<u8>>::try_from_slice(&version).err();
+    assert_eq!(
+        err.unwrap().to_string(),
+        Error::UnsupportedVersion(0).to_string()
+    );
+    // the header is followed by the final node and the current chunk, each made of the lanes,
+    // the offset, the rate and the mode
+    for &mode in [206, 409].iter() {
+        let mut squeezing = buf.clone();
+        squeezing[mode] = Mode::Squeezing as u8;
+        let err = KangarooTwelve::<Vec<u8>>::try_from_slice(&squeezing)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), Error::InvalidState.to_string());
+    }
+    // the current chunk is followed by the customization string and the `written` and
+    // `chunks` counters, which must agree with the offsets of both states
+    let counters = [(1807u64, 1u64), (1809, 1), (1808, 0), (1808, 2), (0, 1)];
+    for &(written, chunks) in counters.iter() {
+        let mut inconsistent = buf.clone();
+        inconsistent[414..422].copy_from_slice(&written.to_le_bytes());
+        inconsistent[422..430].copy_from_slice(&chunks.to_le_bytes());
+        let err = KangarooTwelve::<Vec<u8>>::try_from_slice(&inconsistent)
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), Error::InvalidState.to_string());
+    }
+    assert_eq!(&buf[414..422], &1808u64.to_le_bytes());
+    assert_eq!(&buf[422..430], &1u64.to_le_bytes());
+}
@@ -1,5 +1,5 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use tiny_keccak::{Hasher, Keccak};
+use tari_tiny_keccak::{keccak256_short, Error, Hasher, Keccak};
 #[test]
 fn empty_keccak() {
     let keccak = Keccak::v256();
@@ -47,3 +47,78 @@ fn keccak_save() {
 
     assert_eq!(out, out2);
 }
+
+#[test]
+fn keccak_to_bytes_resumes() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"hello");
+    let bytes = keccak.to_bytes();
+    assert_eq!(0x01, bytes[202]);
+
+    let mut restored: Keccak = Keccak::from_bytes(&bytes).unwrap();
+    keccak.update(b" world");
+    restored.update(b" world");
+    let mut out = [0u8; 32];
+    let mut restored_out = [0u8; 32];
+    keccak.finalize(&mut out);
+    restored.finalize(&mut restored_out);
+    assert_eq!(out, restored_out);
+
+    // a SHA3 state has a different suffix
+    let mut bytes = bytes;
+    bytes[202] = 0x06;
+    assert!(Keccak::<tari_tiny_keccak::KeccakF>::from_bytes(&bytes).is_err());
+}
+
+fn check_keccak256_short<const N: usize>() {
+    let mut input = [0u8; N];
+    for (i, byte) in input.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let mut expected = [0u8; 32];
+    let mut keccak = Keccak::v256();
+    keccak.update(&input);
+    keccak.finalize(&mut expected);
+    assert_eq!(keccak256_short(&input), expected, "{} bytes", N);
+}
+
+#[test]
+fn keccak256_short_matches_keccak_v256() {
+    check_keccak256_short::<0>();
+    check_keccak256_short::<1>();
+    check_keccak256_short::<7>();
+    check_keccak256_short::<8>();
+    check_keccak256_short::<32>();
+    check_keccak256_short::<64>();
+    check_keccak256_short::<135>();
+}
+
+#[test]
+fn keccak_deserialize_rejects_invalid_states() {
+    let mut keccak = Keccak::v256();
+    keccak.update(b"hello");
+    let buf = borsh::to_vec(&keccak).unwrap();
+    assert!(Keccak::try_from_slice(&buf).is_ok());
+
+    // offset past the rate
+    let mut invalid = buf.clone();
+    invalid[200] = 136;
+    assert!(Keccak::try_from_slice(&invalid).is_err());
+
+    // rate out of range
+    for rate in [0, 1, 200, 255].iter() {
+        let mut invalid = buf.clone();
+        invalid[200] = 0;
+        invalid[201] = *rate;
+        assert!(Keccak::try_from_slice(&invalid).is_err());
+    }
+}
+
+#[test]
+fn keccak_try_new() {
+    assert!(Keccak::<tari_tiny_keccak::KeccakF>::try_new(256).is_ok());
+    assert_eq!(
+        Keccak::<tari_tiny_keccak::KeccakF>::try_new(128).err(),
+        Some(Error::InvalidSecurityLevel(128))
+    );
+}
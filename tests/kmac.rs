@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, IntoXof, Kmac, Xof};
+use borsh::{io, BorshDeserialize};
+use tari_tiny_keccak::{Error, Hasher, IntoXof, Kmac, KmacXof, Mode, Xof};
 
 #[test]
 fn test_kmac128_one() {
@@ -331,3 +332,78 @@ fn test_kmac256_xof_three() {
     xof.squeeze(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn test_kmac_encoded_key_fills_block() {
+    // left_encode(168) || encode_string(key) is exactly 168 bytes. The expected output is the one
+    // of `openssl mac -macopt hexkey:4242..42 -macopt size:32 KMAC128` (OpenSSL 3).
+    let key = [0x42u8; 163];
+    let expected = b"\
+        \x9A\xAC\x73\xE8\x36\xF2\x37\xD6\x62\xA6\xCB\xC1\xFD\x33\x76\xD0\
+        \xD8\xCB\x2D\x71\x25\x10\x9A\x34\xE8\xE3\xDE\x54\xBE\x16\x4F\x38\
+    ";
+    let mut output = [0u8; 32];
+    let mut kmac = Kmac::v128(&key, b"");
+    kmac.update(b"abc");
+    kmac.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_kmac_checkpoint() {
+    let mut kmac = Kmac::v256(b"key", b"custom");
+    kmac.update(b"hello");
+    let buf = borsh::to_vec(&kmac).unwrap();
+    assert_eq!(&[1, 1, 0, 1], &buf[..4]);
+    kmac.update(b" world");
+    let mut output = [0u8; 64];
+    kmac.finalize(&mut output);
+
+    let mut restored = Kmac::try_from_slice(&buf).unwrap();
+    restored.update(b" world");
+    let mut output2 = [0u8; 64];
+    restored.finalize(&mut output2);
+    assert_eq!(&output[..], &output2[..]);
+}
+
+#[test]
+fn test_kmac_xof_checkpoint() {
+    let mut kmac = Kmac::v128(b"key", b"");
+    kmac.update(b"hello world");
+    let mut xof = kmac.into_xof();
+    let mut output = [0u8; 400];
+    xof.squeeze(&mut output[..200]);
+    let buf = borsh::to_vec(&xof).unwrap();
+    xof.squeeze(&mut output[200..]);
+
+    let mut restored = KmacXof::try_from_slice(&buf).unwrap();
+    let mut output2 = [0u8; 200];
+    restored.squeeze(&mut output2);
+    assert_eq!(&output[200..], &output2[..]);
+}
+
+#[test]
+fn test_kmac_checkpoint_rejected() {
+    let mut kmac = Kmac::v128(b"key", b"");
+    kmac.update(b"hello");
+    let buf = borsh::to_vec(&kmac).unwrap();
+    let err = |buf: &[u8]| Kmac::try_from_slice(buf).err().unwrap().to_string();
+
+    assert_eq!(
+        KmacXof::try_from_slice(&buf).err().unwrap().to_string(),
+        Error::AlgorithmMismatch.to_string()
+    );
+    let mut version = buf.clone();
+    version[0] = 2;
+    assert_eq!(err(&version), Error::UnsupportedVersion(2).to_string());
+    // a KMAC128 state claimed to be a KMAC256 one
+    let mut level = buf.clone();
+    level[2..4].copy_from_slice(&256u16.to_le_bytes());
+    assert_eq!(err(&level), Error::InvalidState.to_string());
+    // the header and the lanes are followed by the offset, the rate and the mode
+    let mut squeezing = buf.clone();
+    squeezing[206] = Mode::Squeezing as u8;
+    let squeezing = Kmac::try_from_slice(&squeezing).err().unwrap();
+    assert_eq!(squeezing.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(squeezing.to_string(), Error::InvalidState.to_string());
+}
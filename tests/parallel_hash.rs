@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, ParallelHash};
+use borsh::{io, BorshDeserialize};
+use tari_tiny_keccak::{Error, Hasher, IntoXof, KeccakF, Mode, ParallelHash, ParallelHashXof, Xof};
 
 #[test]
 fn test_parallel_hash128_one() {
@@ -121,3 +122,151 @@ fn test_parallel_hash256_three() {
     phash.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|j| (j % 251) as u8).collect()
+}
+
+#[test]
+fn test_parallel_hash128_many_blocks() {
+    let custom_string = b"Parallel Data";
+    let input = pattern(1000);
+    let block_size = 16;
+    let expected = b"\
+        \x82\x26\xe4\x9f\xc6\xe6\x5b\x15\x9b\x4a\xb1\x36\xc9\xdc\xe4\x9d\
+        \x1f\x2f\x35\x8a\x30\xe9\xe4\x1e\x48\x48\xa6\x8f\xb6\x54\x67\xb0\
+    ";
+
+    let mut phash = ParallelHash::v128(custom_string, block_size);
+    let mut output = [0u8; 32];
+    phash.update(&input);
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output);
+
+    let mut phash = ParallelHash::v128(custom_string, block_size);
+    let mut output = [0u8; 32];
+    for chunk in input.chunks(7) {
+        phash.update(chunk);
+    }
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_parallel_hash256_many_blocks() {
+    let custom_string = b"Parallel Data";
+    let input = pattern(1000);
+    let block_size = 16;
+    let expected = b"\
+        \x84\xae\x52\x8b\xec\x35\x02\xab\xe4\x64\xa7\x8b\x6b\xb7\xbb\x2a\
+        \x2c\xc0\xf3\xaa\x30\x61\xca\x01\xcd\xdb\x96\x72\x9c\x86\x2a\x58\
+        \x0c\xb5\x49\x98\x19\xe9\xa0\x9e\xe7\x81\x4f\x22\xbe\x49\x0a\x87\
+        \x31\x31\x0f\x34\x16\x92\x4d\x33\xcf\x44\xfa\xee\x88\x5e\x64\xac\
+    ";
+
+    let mut phash = ParallelHash::v256(custom_string, block_size);
+    let mut output = [0u8; 64];
+    phash.update(&input);
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output[..]);
+
+    let mut phash = ParallelHash::v256(custom_string, block_size);
+    let mut output = [0u8; 64];
+    for chunk in input.chunks(7) {
+        phash.update(chunk);
+    }
+    phash.finalize(&mut output);
+    assert_eq!(expected, &output[..]);
+}
+
+#[test]
+fn test_parallel_hash_try_new() {
+    assert!(ParallelHash::try_v128(b"", 8).is_ok());
+    assert_eq!(
+        ParallelHash::try_v256(b"", 0).err().unwrap(),
+        Error::ZeroBlockSize
+    );
+    assert_eq!(
+        ParallelHash::<KeccakF>::try_new(b"", 8, 512).err().unwrap(),
+        Error::InvalidSecurityLevel(512)
+    );
+}
+
+#[test]
+#[should_panic(expected = "block size cannot be 0")]
+fn test_parallel_hash_zero_block_size() {
+    ParallelHash::v128(b"", 0);
+}
+
+#[test]
+fn test_parallel_hash_checkpoint() {
+    let input = pattern(100);
+    // after 0, 1, 4 and 5 whole blocks, with and without an unfinished block
+    for split in [0, 3, 8, 13, 32, 40, 45].iter() {
+        let mut phash = ParallelHash::v256(b"custom", 8);
+        phash.update(&input[..*split]);
+        let buf = borsh::to_vec(&phash).unwrap();
+        phash.update(&input[*split..]);
+        let mut output = [0u8; 64];
+        phash.finalize(&mut output);
+
+        let mut restored = ParallelHash::try_from_slice(&buf).unwrap();
+        restored.update(&input[*split..]);
+        let mut output2 = [0u8; 64];
+        restored.finalize(&mut output2);
+        assert_eq!(&output[..], &output2[..]);
+    }
+}
+
+#[test]
+fn test_parallel_hash_xof_checkpoint() {
+    let mut phash = ParallelHash::v128(b"", 8);
+    phash.update(&pattern(20));
+    let mut xof = phash.into_xof();
+    let mut output = [0u8; 64];
+    xof.squeeze(&mut output[..32]);
+    let buf = borsh::to_vec(&xof).unwrap();
+    xof.squeeze(&mut output[32..]);
+
+    let mut restored = ParallelHashXof::try_from_slice(&buf).unwrap();
+    let mut output2 = [0u8; 32];
+    restored.squeeze(&mut output2);
+    assert_eq!(&output[32..], &output2[..]);
+}
+
+#[test]
+fn test_parallel_hash_checkpoint_rejected() {
+    let mut phash = ParallelHash::v128(b"", 8);
+    phash.update(&pattern(13));
+    let buf = borsh::to_vec(&phash).unwrap();
+    let err = |buf: &[u8]| ParallelHash::try_from_slice(buf).err().unwrap().to_string();
+
+    assert_eq!(
+        ParallelHashXof::try_from_slice(&buf)
+            .err()
+            .unwrap()
+            .to_string(),
+        Error::AlgorithmMismatch.to_string()
+    );
+    let mut level = buf.clone();
+    level[2..4].copy_from_slice(&256u16.to_le_bytes());
+    assert_eq!(err(&level), Error::InvalidState.to_string());
+    // the header, the main state and its suffix are followed by the block size
+    let mut block_size = buf.clone();
+    block_size[208..216].copy_from_slice(&0u64.to_le_bytes());
+    assert_eq!(err(&block_size), Error::ZeroBlockSize.to_string());
+    // the unfinished block can't be a whole block
+    let mut absorbed = buf.clone();
+    let len = absorbed.len();
+    absorbed[len - 8..].copy_from_slice(&8u64.to_le_bytes());
+    assert_eq!(err(&absorbed), Error::InvalidState.to_string());
+    // neither the main state nor the state of the unfinished block can be squeezing; the mode of
+    // the latter is followed by its suffix and the number of bytes it has absorbed
+    for &mode in [206, len - 10].iter() {
+        let mut squeezing = buf.clone();
+        squeezing[mode] = Mode::Squeezing as u8;
+        let squeezing = ParallelHash::try_from_slice(&squeezing).err().unwrap();
+        assert_eq!(squeezing.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(squeezing.to_string(), Error::InvalidState.to_string());
+    }
+}
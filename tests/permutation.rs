@@ -0,0 +1,159 @@
+use std::cell::Cell;
+use tari_tiny_keccak::{
+    keccakf, Buffer, CShake, Hasher, IntoXof, Keccak, Kmac, ParallelHash, Permutation, Sha3, Shake,
+    TupleHash, Xof,
+};
+
+thread_local! {
+    static INVOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// `keccak-f[1600]` that counts how many times it's invoked by the current thread.
+#[derive(Clone)]
+struct CountingKeccakF;
+
+impl Permutation for CountingKeccakF {
+    fn execute(buffer: &mut Buffer) {
+        INVOCATIONS.with(|invocations| invocations.set(invocations.get() + 1));
+        keccakf(buffer.words());
+    }
+}
+
+/// `keccak-f[1600]` on a state kept with the lanes 1, 2, 8, 12, 17 and 20 complemented.
+#[derive(Clone)]
+struct ComplementedKeccakF;
+
+impl ComplementedKeccakF {
+    fn complement(buffer: &mut Buffer) {
+        for &lane in [1, 2, 8, 12, 17, 20].iter() {
+            buffer.words()[lane] = !buffer.words()[lane];
+        }
+    }
+}
+
+impl Permutation for ComplementedKeccakF {
+    const COMPLEMENTED: bool = true;
+
+    fn execute(_: &mut Buffer) {
+        unreachable!("sponges call execute_complemented when COMPLEMENTED is set");
+    }
+
+    fn execute_complemented(buffer: &mut Buffer) {
+        INVOCATIONS.with(|invocations| invocations.set(invocations.get() + 1));
+        Self::complement(buffer);
+        keccakf(buffer.words());
+        Self::complement(buffer);
+    }
+}
+
+/// Runs `f` and returns its result together with the number of permutations it invoked.
+fn count<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    INVOCATIONS.with(|invocations| invocations.set(0));
+    let result = f();
+    (result, INVOCATIONS.with(|invocations| invocations.get()))
+}
+
+fn finalize<H: Hasher>(mut hasher: H, input: &[u8]) -> [u8; 32] {
+    let mut output = [0u8; 32];
+    hasher.update(input);
+    hasher.finalize(&mut output);
+    output
+}
+
+fn squeeze<X: Xof>(mut xof: X) -> [u8; 400] {
+    let mut output = [0u8; 400];
+    xof.squeeze(&mut output);
+    output
+}
+
+#[test]
+fn test_sha3_uses_permutation() {
+    let input = [0x5a; 200];
+    let (output, invocations) = count(|| finalize(Sha3::<CountingKeccakF>::new(256), &input));
+    assert_eq!(finalize(Sha3::v256(), &input), output);
+    assert_eq!(2, invocations);
+}
+
+#[test]
+fn test_sha3_uses_complemented_permutation() {
+    let input = [0x5a; 200];
+    let (output, invocations) = count(|| finalize(Sha3::<ComplementedKeccakF>::new(256), &input));
+    assert_eq!(finalize(Sha3::v256(), &input), output);
+    assert_eq!(2, invocations);
+
+    let (output, invocations) = count(|| squeeze(Shake::<ComplementedKeccakF>::new(128)));
+    assert_eq!(&squeeze(Shake::v128())[..], &output[..]);
+    assert_eq!(3, invocations);
+}
+
+#[test]
+fn test_keccak_uses_permutation() {
+    let (output, invocations) = count(|| finalize(Keccak::<CountingKeccakF>::new(256), b""));
+    assert_eq!(finalize(Keccak::v256(), b""), output);
+    assert_eq!(1, invocations);
+}
+
+#[test]
+fn test_shake_uses_permutation() {
+    let (output, invocations) = count(|| squeeze(Shake::<CountingKeccakF>::new(128)));
+    assert_eq!(&squeeze(Shake::v128())[..], &output[..]);
+    // 400 bytes of output need three blocks of 168 bytes
+    assert_eq!(3, invocations);
+}
+
+#[test]
+fn test_cshake_uses_permutation() {
+    let (output, invocations) =
+        count(|| finalize(CShake::<CountingKeccakF>::new(b"N", b"C", 128), b"abc"));
+    assert_eq!(finalize(CShake::v128(b"N", b"C"), b"abc"), output);
+    // one block for the name and the customization string, one for the message
+    assert_eq!(2, invocations);
+}
+
+#[test]
+fn test_kmac_uses_permutation() {
+    let (output, invocations) =
+        count(|| finalize(Kmac::<CountingKeccakF>::new(b"key", b"C", 128), b"abc"));
+    assert_eq!(finalize(Kmac::v128(b"key", b"C"), b"abc"), output);
+    // one block for the customization string, one for the key, one for the message
+    assert_eq!(3, invocations);
+
+    let (output, invocations) =
+        count(|| squeeze(Kmac::<CountingKeccakF>::new(b"key", b"C", 128).into_xof()));
+    assert_eq!(
+        &squeeze(Kmac::v128(b"key", b"C").into_xof())[..],
+        &output[..]
+    );
+    assert_eq!(5, invocations);
+}
+
+#[test]
+fn test_tuple_hash_uses_permutation() {
+    let (output, invocations) =
+        count(|| finalize(TupleHash::<CountingKeccakF>::new(b"C", 128), b"abc"));
+    assert_eq!(finalize(TupleHash::v128(b"C"), b"abc"), output);
+    assert_eq!(2, invocations);
+
+    let (output, invocations) =
+        count(|| squeeze(TupleHash::<CountingKeccakF>::new(b"C", 128).into_xof()));
+    assert_eq!(&squeeze(TupleHash::v128(b"C").into_xof())[..], &output[..]);
+    assert_eq!(4, invocations);
+}
+
+#[test]
+fn test_parallel_hash_uses_permutation() {
+    // four blocks are hashed with the four-way permutation, the fifth one on its own
+    let input = [0x5a; 40];
+    let (output, invocations) =
+        count(|| finalize(ParallelHash::<CountingKeccakF>::new(b"C", 8, 128), &input));
+    assert_eq!(finalize(ParallelHash::v128(b"C", 8), &input), output);
+    assert_eq!(7, invocations);
+
+    let (output, invocations) =
+        count(|| squeeze(ParallelHash::<CountingKeccakF>::new(b"C", 8, 128).into_xof()));
+    assert_eq!(
+        &squeeze(ParallelHash::v128(b"C", 8).into_xof())[..],
+        &output[..]
+    );
+    assert_eq!(4, invocations);
+}
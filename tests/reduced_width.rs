@@ -0,0 +1,160 @@
+use tari_tiny_keccak::{keccakf100, keccakf200, keccakf25, keccakf400, keccakf50, keccakf800};
+
+#[test]
+fn keccakf25_zero_state() {
+    let mut state = [0u8; 25];
+    keccakf25(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x0, 0x0, 0x1, 0x1, 0x0, 0x1, 0x1, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
+            0x1, 0x0, 0x1, 0x0, 0x1, 0x0, 0x1, 0x0
+        ]
+    );
+    keccakf25(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x0, 0x0, 0x1, 0x1, 0x1, 0x1, 0x0, 0x0, 0x1, 0x0, 0x1, 0x0, 0x0, 0x1, 0x1, 0x1, 0x1,
+            0x1, 0x1, 0x0, 0x1, 0x0, 0x1, 0x1, 0x1
+        ]
+    );
+}
+
+#[test]
+fn keccakf50_zero_state() {
+    let mut state = [0u8; 25];
+    keccakf50(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x0, 0x2, 0x3, 0x1, 0x1, 0x1, 0x0, 0x3, 0x3, 0x3, 0x1, 0x1, 0x1, 0x3, 0x1, 0x0, 0x1,
+            0x3, 0x2, 0x0, 0x3, 0x0, 0x1, 0x0, 0x2
+        ]
+    );
+    keccakf50(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x1, 0x0, 0x2, 0x3, 0x1, 0x2, 0x3, 0x0, 0x1, 0x2, 0x3, 0x1, 0x1, 0x2, 0x2, 0x3, 0x1,
+            0x0, 0x2, 0x3, 0x3, 0x1, 0x0, 0x2, 0x1
+        ]
+    );
+}
+
+#[test]
+fn keccakf100_zero_state() {
+    let mut state = [0u8; 25];
+    keccakf100(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x6, 0x6, 0x5, 0xc, 0xd, 0xe, 0xb, 0xa, 0xd, 0x6, 0x2, 0xf, 0x0, 0x2, 0x8, 0x5, 0x0,
+            0xd, 0x7, 0x7, 0xe, 0xa, 0xa, 0x0, 0x1
+        ]
+    );
+    keccakf100(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x2, 0x5, 0x5, 0x5, 0x1, 0xc, 0x2, 0x8, 0xe, 0x7, 0xb, 0xe, 0xa, 0xa, 0x9, 0x1, 0xd,
+            0x2, 0x4, 0x2, 0x6, 0x7, 0xd, 0x6, 0x2
+        ]
+    );
+}
+
+#[test]
+fn keccakf200_zero_state() {
+    let mut state = [0u8; 25];
+    keccakf200(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x3c, 0x28, 0x26, 0x84, 0x1c, 0xb3, 0x5c, 0x17, 0x1e, 0xaa, 0xe9, 0xb8, 0x11, 0x13,
+            0x4c, 0xea, 0xa3, 0x85, 0x2c, 0x69, 0xd2, 0xc5, 0xab, 0xaf, 0xea
+        ]
+    );
+    keccakf200(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x1b, 0xef, 0x68, 0x94, 0x92, 0xa8, 0xa5, 0x43, 0xa5, 0x99, 0x9f, 0xdb, 0x83, 0x4e,
+            0x31, 0x66, 0xa1, 0x4b, 0xe8, 0x27, 0xd9, 0x50, 0x40, 0x47, 0x9e
+        ]
+    );
+}
+
+#[test]
+fn keccakf400_zero_state() {
+    let mut state = [0u16; 25];
+    keccakf400(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x09f5, 0x40ac, 0x0fa9, 0x14f5, 0xe89f, 0xeca0, 0x5bd1, 0x7870, 0xeff0, 0xbf8f, 0x0337,
+            0x6052, 0xdc75, 0x0ec9, 0xe776, 0x5246, 0x59a1, 0x5d81, 0x6d95, 0x6e14, 0x633e, 0x58ee,
+            0x71ff, 0x714c, 0xb38e
+        ]
+    );
+    keccakf400(&mut state);
+    assert_eq!(
+        state,
+        [
+            0xe537, 0xd5d6, 0xdbe7, 0xaaf3, 0x9bc7, 0xca7d, 0x86b2, 0xfdec, 0x692c, 0x4e5b, 0x67b1,
+            0x15ad, 0xa7f7, 0xa66f, 0x67ff, 0x3f8a, 0x2f99, 0xe2c2, 0x656b, 0x5f31, 0x5ba6, 0xca29,
+            0xc224, 0xb85c, 0x097c
+        ]
+    );
+}
+
+#[test]
+fn keccakf800_zero_state() {
+    let mut state = [0u32; 25];
+    keccakf800(&mut state);
+    assert_eq!(
+        state,
+        [
+            0xe531d45d, 0xf404c6fb, 0x23a0bf99, 0xf1f8452f, 0x51ffd042, 0xe539f578, 0xf00b80a7,
+            0xaf973664, 0xbf5af34c, 0x227a2424, 0x88172715, 0x9f685884, 0xb15cd054, 0x1bf4fc0e,
+            0x6166fa91, 0x1a9e599a, 0xa3970a1f, 0xab659687, 0xafab8d68, 0xe74b1015, 0x34001a98,
+            0x4119eff3, 0x930a0e76, 0x87b28070, 0x11efe996
+        ]
+    );
+    keccakf800(&mut state);
+    assert_eq!(
+        state,
+        [
+            0x75bf2d0d, 0x9b610e89, 0xc826af40, 0x64cd84ab, 0xf905bdd6, 0xbc832835, 0x5f8001b9,
+            0x15662cce, 0x8e38c95e, 0x701fe543, 0x1b544380, 0x89acdeff, 0x51edb5de, 0x0e9702d9,
+            0x6c19aa16, 0xa2913eee, 0x60754e9a, 0x9819063c, 0xf4709254, 0xd09f9084, 0x772da259,
+            0x1db35df7, 0x5aa60162, 0x358825d5, 0xb3783bab
+        ]
+    );
+}
+
+#[cfg(all(feature = "sponge", feature = "duplex"))]
+#[test]
+fn reduced_width_rejects_oversized_rate() {
+    use tari_tiny_keccak::{
+        Duplex, Error, KeccakF200, KeccakF400, KeccakF800, Permutation, Sponge, SpongeBuilder,
+    };
+
+    fn check<P: Permutation + Copy>(width: usize) {
+        assert_eq!(width, P::WIDTH);
+        let builder: SpongeBuilder<P> = Sponge::builder().permutation();
+        // a capacity of 8 bits is the largest rate the permutation allows
+        assert_eq!(width / 8 - 1, builder.capacity(8).build().rate());
+        assert_eq!(width / 8 - 1, Duplex::<P>::new(8).rate());
+        // a rate of the whole state, or a capacity larger than the state, are rejected
+        for capacity in [0, width + 8].iter() {
+            let sponge = builder.capacity(*capacity).try_build();
+            assert_eq!(Err(Error::RateOutOfRange), sponge.map(|_| ()));
+            let duplex = Duplex::<P>::try_new(*capacity);
+            assert_eq!(Err(Error::RateOutOfRange), duplex.map(|_| ()));
+        }
+    }
+
+    check::<KeccakF200>(200);
+    check::<KeccakF400>(400);
+    check::<KeccakF800>(800);
+}
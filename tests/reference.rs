@@ -0,0 +1,456 @@
+//! Differential tests of the hashers against the bit-level reference implementation.
+
+#![allow(dead_code)]
+
+use quickcheck::quickcheck;
+use tari_tiny_keccak::reference::{self, bits_to_bytes, bytes_to_bits};
+use tari_tiny_keccak::*;
+
+/// Suffix bits of a domain separation byte, that is every bit below its highest set bit, which
+/// is the first bit of the padding.
+fn suffix(delim: u8) -> Vec<bool> {
+    let bits = 7 - delim.leading_zeros() as usize;
+    (0..bits).map(|i| delim >> i & 1 == 1).collect()
+}
+
+fn sponge(rounds: usize, rate: usize, input: &[u8], delim: u8, len: usize) -> Vec<u8> {
+    let mut n = bytes_to_bits(input);
+    n.extend(suffix(delim));
+    bits_to_bytes(&reference::sponge(rounds, 8 * rate, &n, 8 * len))
+}
+
+fn left_encode(x: usize) -> Vec<u8> {
+    let bytes = (x as u64).to_be_bytes();
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(7);
+    let mut encoded = vec![(8 - start) as u8];
+    encoded.extend_from_slice(&bytes[start..]);
+    encoded
+}
+
+fn right_encode(x: usize) -> Vec<u8> {
+    let mut encoded = left_encode(x);
+    encoded.rotate_left(1);
+    encoded
+}
+
+fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(8 * s.len());
+    encoded.extend_from_slice(s);
+    encoded
+}
+
+fn bytepad(x: &[u8], w: usize) -> Vec<u8> {
+    let mut z = left_encode(w);
+    z.extend_from_slice(x);
+    let zeros = (w - z.len() % w) % w;
+    z.resize(z.len() + zeros, 0);
+    z
+}
+
+fn cshake(bits: usize, x: &[u8], len: usize, name: &[u8], custom: &[u8]) -> Vec<u8> {
+    if name.is_empty() && custom.is_empty() {
+        return reference::shake(bits, x, len);
+    }
+    let rate = 200 - bits / 4;
+    let mut input = encode_string(name);
+    input.extend(encode_string(custom));
+    let mut input = bytepad(&input, rate);
+    input.extend_from_slice(x);
+    sponge(24, rate, &input, 0x04, len)
+}
+
+fn hash<H: Hasher>(mut hasher: H, inputs: &[&[u8]], len: usize) -> Vec<u8> {
+    for input in inputs {
+        hasher.update(input);
+    }
+    let mut output = vec![0u8; len];
+    hasher.finalize(&mut output);
+    output
+}
+
+fn squeeze<X: Xof>(mut xof: X, len: usize) -> Vec<u8> {
+    let mut output = vec![0u8; len];
+    let split = len / 3;
+    xof.squeeze(&mut output[..split]);
+    xof.squeeze(&mut output[split..]);
+    output
+}
+
+/// Splits `input` in two parts at `at` modulo the length of `input` plus one.
+fn split(input: &[u8], at: usize) -> [&[u8]; 2] {
+    let at = at % (input.len() + 1);
+    [&input[..at], &input[at..]]
+}
+
+/// Output of `KECCAK[c](M || suffix, d)` for the first `bit_len` bits of `message`, with the
+/// unused high bits of the last byte cleared.
+fn bit_keccak(c: usize, message: &[u8], bit_len: usize, delim: u8, d: usize) -> Vec<u8> {
+    let mut n = bytes_to_bits(message);
+    n.truncate(bit_len);
+    n.extend(suffix(delim));
+    let mut output = reference::keccak(c, &n, d);
+    output.resize(d.div_ceil(8) * 8, false);
+    bits_to_bytes(&output)
+}
+
+/// An arbitrary message of 1600 bits, whose prefixes are hashed at every bit length from 0 to
+/// 1600. It is not one of the messages of the NIST test vectors.
+fn bit_message() -> Vec<u8> {
+    (0..200u32).map(|i| (i * 167 + 13) as u8).collect()
+}
+
+#[test]
+fn test_keccakf() {
+    fn prop(state: Vec<u64>) -> bool {
+        let mut expected = [0u64; 25];
+        for (lane, value) in expected.iter_mut().zip(state) {
+            *lane = value;
+        }
+        let mut state = expected;
+        keccakf(&mut state);
+        reference::keccakf(&mut expected);
+        expected == state
+    }
+    quickcheck(prop as fn(Vec<u64>) -> bool);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn test_keccak() {
+    fn prop(input: Vec<u8>, at: usize) -> bool {
+        let input = split(&input, at);
+        let message = input.concat();
+        [
+            (Keccak::v224(), 224),
+            (Keccak::v256(), 256),
+            (Keccak::v384(), 384),
+            (Keccak::v512(), 512),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            let expected = sponge(24, 200 - bits / 4, &message, 0x01, bits / 8);
+            hash(hasher.clone(), &input, bits / 8) == expected
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, usize) -> bool);
+}
+
+#[cfg(feature = "keccak")]
+#[test]
+fn test_keccak_bits() {
+    let message = bit_message();
+    for bit_len in 0..=1600 {
+        let mut keccak = Keccak::v256();
+        keccak.update_bits(&message, bit_len);
+        let mut output = [0u8; 32];
+        keccak.finalize(&mut output);
+        let expected = bit_keccak(512, &message, bit_len, 0x01, 256);
+        assert_eq!(&output[..], &expected[..], "Keccak-256 of {} bits", bit_len);
+    }
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_sha3() {
+    fn prop(input: Vec<u8>, at: usize) -> bool {
+        let input = split(&input, at);
+        let message = input.concat();
+        [
+            (Sha3::v224(), 224),
+            (Sha3::v256(), 256),
+            (Sha3::v384(), 384),
+            (Sha3::v512(), 512),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            hash(hasher.clone(), &input, bits / 8) == reference::sha3(*bits, &message)
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, usize) -> bool);
+}
+
+#[cfg(feature = "sha3")]
+#[test]
+fn test_sha3_bits() {
+    let message = bit_message();
+    let hashers = [
+        (Sha3::v224(), 224),
+        (Sha3::v256(), 256),
+        (Sha3::v384(), 384),
+        (Sha3::v512(), 512),
+    ];
+    for bit_len in 0..=1600 {
+        for (sha3, bits) in hashers.iter() {
+            let mut sha3 = sha3.clone();
+            sha3.update_bits(&message, bit_len);
+            let mut output = vec![0u8; bits / 8];
+            sha3.finalize(&mut output);
+            let expected = bit_keccak(2 * bits, &message, bit_len, 0x06, *bits);
+            assert_eq!(output, expected, "SHA3-{} of {} bits", bits, bit_len);
+        }
+    }
+}
+
+#[cfg(feature = "shake")]
+#[test]
+fn test_shake() {
+    fn prop(input: Vec<u8>, at: usize, len: u16) -> bool {
+        let input = split(&input, at);
+        let message = input.concat();
+        let len = len as usize % 500;
+        [(Shake::v128(), 128), (Shake::v256(), 256)]
+            .iter()
+            .all(|(hasher, bits)| {
+                let mut hasher = hasher.clone();
+                for input in input.iter() {
+                    hasher.update(input);
+                }
+                squeeze(hasher, len) == reference::shake(*bits, &message, len)
+            })
+    }
+    quickcheck(prop as fn(Vec<u8>, usize, u16) -> bool);
+}
+
+#[cfg(feature = "shake")]
+#[test]
+fn test_shake_bits() {
+    let message = bit_message();
+    let hashers = [(Shake::v128(), 128), (Shake::v256(), 256)];
+    for bit_len in 0..=1600 {
+        for (shake, bits) in hashers.iter() {
+            let mut shake = shake.clone();
+            shake.update_bits(&message, bit_len);
+            let out_len = 1 + bit_len % 1347;
+            let mut output = vec![0u8; out_len.div_ceil(8)];
+            shake.squeeze_bits(&mut output, out_len);
+            let expected = bit_keccak(2 * bits, &message, bit_len, 0x1f, out_len);
+            assert_eq!(output, expected, "SHAKE{} of {} bits", bits, bit_len);
+        }
+    }
+}
+
+#[cfg(feature = "cshake")]
+#[test]
+fn test_cshake() {
+    fn prop(input: Vec<u8>, name: Vec<u8>, custom: Vec<u8>, len: u8) -> bool {
+        let len = len as usize;
+        [
+            (CShake::v128(&name, &custom), 128),
+            (CShake::v256(&name, &custom), 256),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            hash(hasher.clone(), &[&input], len) == cshake(*bits, &input, len, &name, &custom)
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>, Vec<u8>, u8) -> bool);
+}
+
+#[cfg(feature = "cshake")]
+#[test]
+fn test_cshake_bits() {
+    let message = bit_message();
+    for bit_len in 0..=1600 {
+        let mut cshake = CShake::v256(b"name", b"custom");
+        cshake.update_bits(&message, bit_len);
+        let mut output = [0u8; 32];
+        cshake.finalize(&mut output);
+        let mut n = bytepad(
+            &[encode_string(b"name"), encode_string(b"custom")].concat(),
+            136,
+        );
+        let start = n.len();
+        n.extend_from_slice(&message);
+        let expected = bit_keccak(512, &n, 8 * start + bit_len, 0x04, 256);
+        assert_eq!(&output[..], &expected[..], "cSHAKE256 of {} bits", bit_len);
+    }
+}
+
+#[cfg(feature = "kmac")]
+#[test]
+fn test_kmac() {
+    fn reference_kmac(
+        bits: usize,
+        key: &[u8],
+        x: &[u8],
+        len: usize,
+        custom: &[u8],
+        xof: bool,
+    ) -> Vec<u8> {
+        let mut input = bytepad(&encode_string(key), 200 - bits / 4);
+        input.extend_from_slice(x);
+        input.extend(right_encode(if xof { 0 } else { 8 * len }));
+        cshake(bits, &input, len, b"KMAC", custom)
+    }
+
+    fn prop(key: Vec<u8>, input: Vec<u8>, custom: Vec<u8>, len: u8) -> bool {
+        let len = len as usize;
+        [
+            (Kmac::v128(&key, &custom), 128),
+            (Kmac::v256(&key, &custom), 256),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            let mut xof = hasher.clone();
+            xof.update(&input);
+            hash(hasher.clone(), &[&input], len)
+                == reference_kmac(*bits, &key, &input, len, &custom, false)
+                && squeeze(xof.into_xof(), len)
+                    == reference_kmac(*bits, &key, &input, len, &custom, true)
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, Vec<u8>, Vec<u8>, u8) -> bool);
+}
+
+#[cfg(feature = "tuple_hash")]
+#[test]
+fn test_tuple_hash() {
+    fn prop(inputs: Vec<Vec<u8>>, custom: Vec<u8>, len: u8) -> bool {
+        let len = len as usize;
+        let inputs: Vec<&[u8]> = inputs.iter().map(|input| input.as_slice()).collect();
+        let mut encoded: Vec<u8> = inputs
+            .iter()
+            .flat_map(|input| encode_string(input))
+            .collect();
+        encoded.extend(right_encode(8 * len));
+        [
+            (TupleHash::v128(&custom), 128),
+            (TupleHash::v256(&custom), 256),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            hash(hasher.clone(), &inputs, len)
+                == cshake(*bits, &encoded, len, b"TupleHash", &custom)
+        })
+    }
+    quickcheck(prop as fn(Vec<Vec<u8>>, Vec<u8>, u8) -> bool);
+}
+
+#[cfg(feature = "parallel_hash")]
+#[test]
+fn test_parallel_hash() {
+    fn reference_parallel_hash(
+        bits: usize,
+        x: &[u8],
+        block_size: usize,
+        len: usize,
+        custom: &[u8],
+    ) -> Vec<u8> {
+        let mut z = left_encode(block_size);
+        let blocks = x.chunks(block_size);
+        let n = blocks.len();
+        for block in blocks {
+            z.extend(cshake(bits, block, bits / 4, b"", b""));
+        }
+        z.extend(right_encode(n));
+        z.extend(right_encode(8 * len));
+        cshake(bits, &z, len, b"ParallelHash", custom)
+    }
+
+    fn prop(input: Vec<u8>, at: usize, block_size: u8, custom: Vec<u8>, len: u8) -> bool {
+        let block_size = 1 + block_size as usize % 16;
+        let len = len as usize;
+        let message = input.clone();
+        let input = split(&input, at);
+        [
+            (ParallelHash::v128(&custom, block_size), 128),
+            (ParallelHash::v256(&custom, block_size), 256),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            hash(hasher.clone(), &input, len)
+                == reference_parallel_hash(*bits, &message, block_size, len, &custom)
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, usize, u8, Vec<u8>, u8) -> bool);
+}
+
+#[cfg(feature = "turbo_shake")]
+#[test]
+fn test_turbo_shake() {
+    fn prop(input: Vec<u8>, at: usize, domain: u8, len: u16) -> bool {
+        let domain = 1 + domain % 0x7f;
+        let len = len as usize % 500;
+        let message = input.clone();
+        let input = split(&input, at);
+        [
+            (TurboShake::v128(domain), 128),
+            (TurboShake::v256(domain), 256),
+        ]
+        .iter()
+        .all(|(hasher, bits)| {
+            let mut hasher = hasher.clone();
+            for input in input.iter() {
+                hasher.update(input);
+            }
+            squeeze(hasher, len) == sponge(12, 200 - bits / 4, &message, domain, len)
+        })
+    }
+    quickcheck(prop as fn(Vec<u8>, usize, u8, u16) -> bool);
+}
+
+#[cfg(feature = "k12")]
+fn length_encode(x: usize) -> Vec<u8> {
+    let bytes = (x as u64).to_be_bytes();
+    let start = bytes.iter().position(|byte| *byte != 0).unwrap_or(8);
+    let mut encoded = bytes[start..].to_vec();
+    encoded.push((8 - start) as u8);
+    encoded
+}
+
+#[cfg(feature = "k12")]
+fn reference_kangaroo(
+    bits: usize,
+    rounds: usize,
+    input: &[u8],
+    custom: &[u8],
+    len: usize,
+) -> Vec<u8> {
+    const CHUNK: usize = 8192;
+    let rate = 200 - bits / 4;
+    let mut s = input.to_vec();
+    s.extend_from_slice(custom);
+    s.extend(length_encode(custom.len()));
+    if s.len() <= CHUNK {
+        return sponge(rounds, rate, &s, 0x07, len);
+    }
+    let mut chunks = s.chunks(CHUNK);
+    let mut node = chunks.next().unwrap().to_vec();
+    node.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+    let n = chunks.len();
+    for chunk in chunks {
+        node.extend(sponge(rounds, rate, chunk, 0x0b, bits / 4));
+    }
+    node.extend(length_encode(n));
+    node.extend_from_slice(&[0xff, 0xff]);
+    sponge(rounds, rate, &node, 0x06, len)
+}
+
+#[cfg(feature = "k12")]
+#[test]
+fn test_kangaroo() {
+    fn prop(input: Vec<u8>, at: usize, custom: Vec<u8>, len: u8) -> bool {
+        let len = len as usize;
+        let message = input.clone();
+        let input = split(&input, at);
+        hash(KangarooTwelve::new(&custom), &input, len)
+            == reference_kangaroo(128, 12, &message, &custom, len)
+            && hash(KangarooTwelve256::new(&custom), &input, len)
+                == reference_kangaroo(256, 12, &message, &custom, len)
+            && hash(MarsupilamiFourteen::new(&custom), &input, len)
+                == reference_kangaroo(256, 14, &message, &custom, len)
+    }
+    quickcheck(prop as fn(Vec<u8>, usize, Vec<u8>, u8) -> bool);
+}
+
+#[cfg(feature = "k12")]
+#[test]
+fn test_kangaroo_many_chunks() {
+    let input: Vec<u8> = (0..5 * 8192 + 17).map(|i| (i % 251) as u8).collect();
+    let input = split(&input, 8192 + 3);
+    let message = input.concat();
+    assert_eq!(
+        hash(KangarooTwelve::new(b"custom"), &input, 32),
+        reference_kangaroo(128, 12, &message, b"custom", 32)
+    );
+}
@@ -0,0 +1,263 @@
+use serde::de::value::{BytesDeserializer, Error, SeqDeserializer, U8Deserializer};
+use serde::de::DeserializeOwned;
+use serde::ser::{self, Impossible, Serialize, SerializeTuple};
+use serde::Deserialize;
+use tari_tiny_keccak::{
+    Buffer, CShake, Duplex, Hasher, IntoXof, KangarooTwelve, KangarooTwelve256, Keccak, Kmac,
+    MarsupilamiFourteen, Mode, OutputMode, ParallelHash, Sha3, Shake, Sponge, TupleHash,
+    TurboShake, Xof,
+};
+
+/// What the types of the crate serialize into.
+#[derive(Debug, PartialEq)]
+enum Value {
+    U8(u8),
+    U64(u64),
+    Bytes(Vec<u8>),
+    Tuple(Vec<u64>),
+}
+
+/// A serializer that records the few data types used by the crate.
+struct Recorder;
+
+struct TupleRecorder(Vec<u64>);
+
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Error> {
+                Err(ser::Error::custom(stringify!($method)))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for Recorder {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = Impossible<Value, Error>;
+    type SerializeTuple = TupleRecorder;
+    type SerializeTupleStruct = Impossible<Value, Error>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+    type SerializeMap = Impossible<Value, Error>;
+    type SerializeStruct = Impossible<Value, Error>;
+    type SerializeStructVariant = Impossible<Value, Error>;
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::U64(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<TupleRecorder, Error> {
+        Ok(TupleRecorder(Vec::with_capacity(len)))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<Value, Error> {
+        Err(ser::Error::custom("serialize_some"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, Error> {
+        Err(ser::Error::custom("serialize_newtype_struct"))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, Error> {
+        Err(ser::Error::custom("serialize_newtype_variant"))
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> Value;
+        serialize_i8(i8) -> Value;
+        serialize_i16(i16) -> Value;
+        serialize_i32(i32) -> Value;
+        serialize_i64(i64) -> Value;
+        serialize_u16(u16) -> Value;
+        serialize_u32(u32) -> Value;
+        serialize_f32(f32) -> Value;
+        serialize_f64(f64) -> Value;
+        serialize_char(char) -> Value;
+        serialize_str(&str) -> Value;
+        serialize_none() -> Value;
+        serialize_unit() -> Value;
+        serialize_unit_struct(&'static str) -> Value;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Value;
+        serialize_seq(Option<usize>) -> Impossible<Value, Error>;
+        serialize_tuple_struct(&'static str, usize) -> Impossible<Value, Error>;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Impossible<Value, Error>;
+        serialize_map(Option<usize>) -> Impossible<Value, Error>;
+        serialize_struct(&'static str, usize) -> Impossible<Value, Error>;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Impossible<Value, Error>;
+    }
+}
+
+impl SerializeTuple for TupleRecorder {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        match value.serialize(Recorder)? {
+            Value::U64(v) => {
+                self.0.push(v);
+                Ok(())
+            }
+            _ => Err(ser::Error::custom("tuples are made of lanes")),
+        }
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Tuple(self.0))
+    }
+}
+
+fn to_bytes<T: Serialize>(value: &T) -> Vec<u8> {
+    match value.serialize(Recorder).unwrap() {
+        Value::Bytes(bytes) => bytes,
+        value => panic!("expected bytes, got {:?}", value),
+    }
+}
+
+/// Restores a value from its bytes, given as bytes as in CBOR and as a sequence as in JSON.
+fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let bytes = to_bytes(value);
+    let seq = SeqDeserializer::<_, Error>::new(bytes.clone().into_iter());
+    let from_seq = T::deserialize(seq).unwrap();
+    assert_eq!(bytes, to_bytes(&from_seq));
+    T::deserialize(BytesDeserializer::<Error>::new(&bytes)).unwrap()
+}
+
+fn check_hasher<H: Hasher + Serialize + DeserializeOwned>(mut hasher: H, output_len: usize) {
+    let input: Vec<u8> = (0..20000).map(|i| (i % 251) as u8).collect();
+    hasher.update(&input[..10000]);
+    let mut restored = round_trip(&hasher);
+
+    let mut output = vec![0u8; output_len];
+    hasher.update(&input[10000..]);
+    hasher.finalize(&mut output);
+    let mut output2 = vec![0u8; output_len];
+    restored.update(&input[10000..]);
+    restored.finalize(&mut output2);
+    assert_eq!(output, output2);
+}
+
+fn check_xof<X: Xof + Serialize + DeserializeOwned>(mut xof: X) {
+    let mut output = [0u8; 500];
+    xof.squeeze(&mut output[..250]);
+    let mut restored = round_trip(&xof);
+
+    xof.squeeze(&mut output[250..]);
+    let mut output2 = [0u8; 250];
+    restored.squeeze(&mut output2);
+    assert_eq!(&output[250..], &output2[..]);
+}
+
+#[test]
+fn serde_buffer_and_mode() {
+    let mut buffer = Buffer::default();
+    for (i, lane) in buffer.words().iter_mut().enumerate() {
+        *lane = i as u64 * 0x0123_4567_89ab_cdef;
+    }
+    let lanes = match buffer.serialize(Recorder).unwrap() {
+        Value::Tuple(lanes) => lanes,
+        value => panic!("expected a tuple, got {:?}", value),
+    };
+    assert_eq!(&lanes[..], &buffer.words()[..]);
+    let mut restored =
+        Buffer::deserialize(SeqDeserializer::<_, Error>::new(lanes.into_iter())).unwrap();
+    assert_eq!(restored.words(), buffer.words());
+    let short = SeqDeserializer::<_, Error>::new(vec![0u64; 24].into_iter());
+    assert!(Buffer::deserialize(short).is_err());
+
+    assert_eq!(Mode::Squeezing.serialize(Recorder).unwrap(), Value::U8(2));
+    let mode = Mode::deserialize(U8Deserializer::<Error>::new(1)).unwrap();
+    assert_eq!(mode as u8, Mode::Absorbing as u8);
+    assert!(Mode::deserialize(U8Deserializer::<Error>::new(3)).is_err());
+}
+
+#[test]
+fn serde_fips202() {
+    check_hasher(Keccak::v256(), 32);
+    check_hasher(Sha3::v512(), 64);
+    check_hasher(Shake::v128(), 100);
+    check_xof(Shake::v256());
+    check_hasher(TurboShake::v128(0x1f), 100);
+    check_xof(TurboShake::v256(0x07));
+}
+
+#[test]
+fn serde_sp800() {
+    check_hasher(CShake::v128(b"name", b"custom"), 100);
+    check_xof(CShake::v256(b"", b""));
+    check_hasher(Kmac::v256(b"key", b"custom"), 64);
+    check_xof(Kmac::v128(b"key", b"").into_xof());
+    check_hasher(TupleHash::v128(b"custom"), 32);
+    check_xof(TupleHash::v256(b"").into_xof());
+    check_hasher(ParallelHash::v256(b"custom", 300), 64);
+    check_xof(ParallelHash::v128(b"", 8).into_xof());
+}
+
+#[test]
+fn serde_kangaroo() {
+    check_hasher(KangarooTwelve::<Vec<u8>>::new(b"custom".to_vec()), 32);
+    check_xof(KangarooTwelve::new(b"".to_vec()).into_xof());
+    check_hasher(KangarooTwelve256::<Vec<u8>>::new(Vec::new()), 64);
+    check_xof(KangarooTwelve256::new(Vec::new()).into_xof());
+    check_hasher(MarsupilamiFourteen::<Vec<u8>>::new(Vec::new()), 64);
+    check_xof(MarsupilamiFourteen::new(Vec::new()).into_xof());
+}
+
+#[test]
+fn serde_sponge_and_duplex() {
+    let keccak288 = Sponge::builder()
+        .capacity(576)
+        .output(OutputMode::Fixed(36))
+        .build();
+    check_hasher(keccak288, 36);
+    check_hasher(Sponge::builder().suffix(0b11, 2).build(), 100);
+    check_xof(Sponge::builder().capacity(1024).build());
+
+    let mut duplex = Duplex::v128();
+    duplex.duplexing(b"key", &mut []);
+    let mut restored = round_trip(&duplex);
+    let mut output = [0u8; 32];
+    let mut output2 = [0u8; 32];
+    duplex.duplexing(b"message", &mut output);
+    restored.duplexing(b"message", &mut output2);
+    assert_eq!(output, output2);
+}
+
+#[test]
+fn serde_rejects_invalid_states() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"hello");
+    let bytes = to_bytes(&sha3);
+    // a SHA3-256 state is not a Keccak-256 nor a SHAKE256 state
+    assert!(Keccak::deserialize(BytesDeserializer::<Error>::new(&bytes)).is_err());
+    assert!(Shake::deserialize(BytesDeserializer::<Error>::new(&bytes)).is_err());
+    assert!(Sha3::deserialize(BytesDeserializer::<Error>::new(&bytes[..203])).is_err());
+    // trailing bytes are rejected too
+    let mut long = bytes.clone();
+    long.push(0);
+    assert!(Sha3::deserialize(BytesDeserializer::<Error>::new(&long)).is_err());
+
+    let kmac = to_bytes(&Kmac::v128(b"key", b""));
+    let err = TupleHash::deserialize(BytesDeserializer::<Error>::new(&kmac)).err();
+    assert!(err.unwrap().to_string().contains("another algorithm"));
+    let turbo = to_bytes(&TurboShake::v128(0x1f));
+    assert!(TurboShake::deserialize(BytesDeserializer::<Error>::new(&turbo[..200])).is_err());
+}
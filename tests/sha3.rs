@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, Sha3};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tari_tiny_keccak::{sha3_256_short, Error, Hasher, Keccak, KeccakF, Sha3, Shake, STATE_BYTES};
 
 #[test]
 fn empty_sha3_256() {
@@ -92,3 +93,303 @@ fn long_string_sha3_512_parts() {
     sha3.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn sha3_to_bytes_layout() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"abc");
+    let bytes = sha3.to_bytes();
+    assert_eq!(STATE_BYTES, bytes.len());
+    assert_eq!(b"abc", &bytes[..3]);
+    assert!(bytes[3..200].iter().all(|byte| *byte == 0));
+    // rate, offset, suffix and mode
+    assert_eq!([136, 3, 0x06, 1], bytes[200..]);
+}
+
+#[test]
+fn sha3_from_bytes_resumes() {
+    let mut sha3 = Sha3::v512();
+    sha3.update(&[0x5a; 100]);
+    let mut restored: Sha3 = Sha3::from_bytes(&sha3.to_bytes()).unwrap();
+    sha3.update(b"hello");
+    restored.update(b"hello");
+
+    let mut output = [0u8; 64];
+    let mut restored_output = [0u8; 64];
+    sha3.finalize(&mut output);
+    restored.finalize(&mut restored_output);
+    assert_eq!(&output[..], &restored_output[..]);
+}
+
+#[test]
+fn sha3_from_bytes_rejects_invalid_states() {
+    let bytes = Sha3::v256().to_bytes();
+    let with = |index: usize, value: u8| {
+        let mut bytes = bytes;
+        bytes[index] = value;
+        Sha3::<KeccakF>::from_bytes(&bytes).is_err()
+    };
+    // a rate of no security level
+    assert!(with(200, 100));
+    // an offset past the rate
+    assert!(with(201, 136));
+    // the suffix of Keccak
+    assert!(with(202, 0x01));
+    // an unknown mode
+    assert!(with(203, 4));
+    assert!(!with(203, 2));
+    assert!(!with(203, 3));
+}
+
+/// The messages of 5, 30, 1605 and 1630 bits of the NIST examples of SHA-3 with bit-oriented
+/// messages, with their bits packed least significant bit first.
+fn nist_bit_messages() -> Vec<(Vec<u8>, usize)> {
+    let mut m1605 = vec![0xa3; 200];
+    m1605.push(0x03);
+    let mut m1630 = vec![0xa3; 203];
+    m1630.push(0x23);
+    vec![
+        (vec![0x13], 5),
+        (vec![0x53, 0x58, 0x7b, 0x19], 30),
+        (m1605, 1605),
+        (m1630, 1630),
+    ]
+}
+
+/// The digests of the NIST examples of SHA-3 with bit-oriented messages
+/// (SHA3-224_Msg5.pdf to SHA3-512_1630.pdf on the NIST "Cryptographic Standards and Guidelines:
+/// Examples with Intermediate Values" page), for the messages of `nist_bit_messages`.
+#[test]
+fn bits_sha3_nist_examples() {
+    type Case = (fn() -> Sha3, [&'static [u8]; 4]);
+    let cases: [Case; 4] = [
+        (
+            Sha3::v224,
+            [
+                b"\
+                    \xff\xba\xd5\xda\x96\xba\xd7\x17\x89\x33\x02\x06\xdc\x67\x68\xec\
+                    \xae\xb1\xb3\x2d\xca\x6b\x33\x01\x48\x96\x74\xab\
+                ",
+                b"\
+                    \xd6\x66\xa5\x14\xcc\x9d\xba\x25\xac\x1b\xa6\x9e\xd3\x93\x04\x60\
+                    \xde\xaa\xc9\x85\x1b\x5f\x0b\xaa\xb0\x07\xdf\x3b\
+                ",
+                b"\
+                    \x22\xd2\xf7\xbb\x0b\x17\x3f\xd8\xc1\x96\x86\xf9\x17\x31\x66\xe3\
+                    \xee\x62\x73\x80\x47\xd7\xea\xdd\x69\xef\xb2\x28\
+                ",
+                b"\
+                    \x4e\x90\x7b\xb1\x05\x78\x61\xf2\x00\xa5\x99\xe9\xd4\xf8\x5b\x02\
+                    \xd8\x84\x53\xbf\x5b\x8a\xce\x9a\xc5\x89\x13\x4c\
+                ",
+            ],
+        ),
+        (
+            Sha3::v256,
+            [
+                b"\
+                    \x7b\x00\x47\xcf\x5a\x45\x68\x82\x36\x3c\xbf\x0f\xb0\x53\x22\xcf\
+                    \x65\xf4\xb7\x05\x9a\x46\x36\x5e\x83\x01\x32\xe3\xb5\xd9\x57\xaf\
+                ",
+                b"\
+                    \xc8\x24\x2f\xef\x40\x9e\x5a\xe9\xd1\xf1\xc8\x57\xae\x4d\xc6\x24\
+                    \xb9\x2b\x19\x80\x9f\x62\xaa\x8c\x07\x41\x1c\x54\xa0\x78\xb1\xd0\
+                ",
+                b"\
+                    \x81\xee\x76\x9b\xed\x09\x50\x86\x2b\x1d\xdd\xed\x2e\x84\xaa\xa6\
+                    \xab\x7b\xfd\xd3\xce\xaa\x47\x1b\xe3\x11\x63\xd4\x03\x36\x36\x3c\
+                ",
+                b"\
+                    \x52\x86\x0a\xa3\x01\x21\x4c\x61\x0d\x92\x2a\x6b\x6c\xab\x98\x1c\
+                    \xcd\x06\x01\x2e\x54\xef\x68\x9d\x74\x40\x21\xe7\x38\xb9\xed\x20\
+                ",
+            ],
+        ),
+        (
+            Sha3::v384,
+            [
+                b"\
+                    \x73\x7c\x9b\x49\x18\x85\xe9\xbf\x74\x28\xe7\x92\x74\x1a\x7b\xf8\
+                    \xdc\xa9\x65\x34\x71\xc3\xe1\x48\x47\x3f\x2c\x23\x6b\x6a\x0a\x64\
+                    \x55\xeb\x1d\xce\x9f\x77\x9b\x4b\x6b\x23\x7f\xef\x17\x1b\x1c\x64\
+                ",
+                b"\
+                    \x95\x5b\x4d\xd1\xbe\x03\x26\x1b\xd7\x6f\x80\x7a\x7e\xfd\x43\x24\
+                    \x35\xc4\x17\x36\x28\x11\xb8\xa5\x0c\x56\x4e\x7e\xe9\x58\x5e\x1a\
+                    \xc7\x62\x6d\xde\x2f\xdc\x03\x0f\x87\x61\x96\xea\x26\x7f\x08\xc3\
+                ",
+                b"\
+                    \xa3\x1f\xdb\xd8\xd5\x76\x55\x1c\x21\xfb\x11\x91\xb5\x4b\xda\x65\
+                    \xb6\xc5\xfe\x97\xf0\xf4\xa6\x91\x03\x42\x4b\x43\xf7\xfd\xb8\x35\
+                    \x97\x9f\xdb\xea\xe8\xb3\xfe\x16\xcb\x82\xe5\x87\x38\x1e\xb6\x24\
+                ",
+                b"\
+                    \x34\x85\xd3\xb2\x80\xbd\x38\x4c\xf4\xa7\x77\x84\x4e\x94\x67\x81\
+                    \x73\x05\x5d\x1c\xbc\x40\xc7\xc2\xc3\x83\x3d\x9e\xf1\x23\x45\x17\
+                    \x2d\x6f\xcd\x31\x92\x3b\xb8\x79\x5a\xc8\x18\x47\xd3\xd8\x85\x5c\
+                ",
+            ],
+        ),
+        (
+            Sha3::v512,
+            [
+                b"\
+                    \xa1\x3e\x01\x49\x41\x14\xc0\x98\x00\x62\x2a\x70\x28\x8c\x43\x21\
+                    \x21\xce\x70\x03\x9d\x75\x3c\xad\xd2\xe0\x06\xe4\xd9\x61\xcb\x27\
+                    \x54\x4c\x14\x81\xe5\x81\x4b\xdc\xeb\x53\xbe\x67\x33\xd5\xe0\x99\
+                    \x79\x5e\x5e\x81\x91\x8a\xdd\xb0\x58\xe2\x2a\x9f\x24\x88\x3f\x37\
+                ",
+                b"\
+                    \x98\x34\xc0\x5a\x11\xe1\xc5\xd3\xda\x9c\x74\x0e\x1c\x10\x6d\x9e\
+                    \x59\x0a\x0e\x53\x0b\x6f\x6a\xaa\x78\x30\x52\x5d\x07\x5c\xa5\xdb\
+                    \x1b\xd8\xa6\xaa\x98\x1a\x28\x61\x3a\xc3\x34\x93\x4a\x01\x82\x3c\
+                    \xd4\x5f\x45\xe4\x9b\x6d\x7e\x69\x17\xf2\xf1\x67\x78\x06\x7b\xab\
+                ",
+                b"\
+                    \xfc\x4a\x16\x7c\xcb\x31\xa9\x37\xd6\x98\xfd\xe8\x2b\x04\x34\x8c\
+                    \x95\x39\xb2\x8f\x0c\x9d\x3b\x45\x05\x70\x9c\x03\x81\x23\x50\xe4\
+                    \x99\x0e\x96\x22\x97\x4f\x6e\x57\x5c\x47\x86\x1c\x0d\x2e\x63\x8c\
+                    \xcf\xc2\x02\x3c\x36\x5b\xb6\x0a\x93\xf5\x28\x55\x06\x98\x78\x6b\
+                ",
+                b"\
+                    \xcf\x9a\x30\xac\x1f\x1f\x6a\xc0\x91\x6f\x9f\xef\x19\x19\xc5\x95\
+                    \xde\xbe\x2e\xe8\x0c\x85\x42\x12\x10\xfd\xf0\x5f\x1c\x6a\xf7\x3a\
+                    \xa9\xca\xc8\x81\xd0\xf9\x1d\xb6\xd0\x34\xa2\xbb\xad\xc1\xcf\x7f\
+                    \xbc\xb2\xec\xfa\x9d\x19\x1d\x3a\x50\x16\xfb\x3f\xad\x87\x09\xc9\
+                ",
+            ],
+        ),
+    ];
+    for (new, expected) in cases.iter() {
+        for ((input, bit_len), expected) in nist_bit_messages().iter().zip(expected.iter()) {
+            let mut sha3 = new();
+            let mut output = vec![0u8; expected.len()];
+            sha3.update_bits(input, *bit_len);
+            sha3.finalize(&mut output);
+            assert_eq!(expected, &&output[..], "{} bits", bit_len);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after a message that is not a whole number of bytes")]
+fn update_after_partial_byte_panics() {
+    let mut sha3 = Sha3::v256();
+    sha3.update_bits(&[0x13], 5);
+    sha3.update(b"more");
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after a message that is not a whole number of bytes")]
+fn update_bits_after_partial_byte_panics() {
+    let mut sha3 = Sha3::v256();
+    sha3.update_bits(&[0x53, 0x58, 0x7b, 0x19], 30);
+    sha3.update_bits(&[0xff], 8);
+}
+
+#[test]
+fn checkpoint_after_partial_byte() {
+    let mut sha3 = Sha3::v256();
+    sha3.update_bits(&[0x53, 0x58, 0x7b, 0x19], 30);
+    let bytes = sha3.to_bytes();
+    // the mode of a squeezing state after trailing bits
+    assert_eq!(3, bytes[203]);
+    // the Borsh encoding has the offset and the rate before the mode
+    let buf = borsh::to_vec(&sha3).unwrap();
+    assert_eq!(3, buf[202]);
+
+    let mut output = [0u8; 32];
+    sha3.clone().finalize(&mut output);
+    for restored in [
+        Sha3::from_bytes(&bytes).unwrap(),
+        Sha3::try_from_slice(&buf).unwrap(),
+    ] {
+        assert_eq!(bytes, restored.to_bytes());
+        let mut restored_output = [0u8; 32];
+        restored.finalize(&mut restored_output);
+        assert_eq!(output, restored_output);
+    }
+}
+
+#[test]
+#[should_panic(expected = "cannot absorb after a message that is not a whole number of bytes")]
+fn update_after_restored_partial_byte_panics() {
+    let mut sha3 = Sha3::v256();
+    sha3.update_bits(&[0x13], 5);
+    let mut restored: Sha3 = Sha3::from_bytes(&sha3.to_bytes()).unwrap();
+    restored.update(b"more");
+}
+
+fn check_sha3_256_short<const N: usize>() {
+    let mut input = [0u8; N];
+    for (i, byte) in input.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let mut expected = [0u8; 32];
+    let mut sha3 = Sha3::v256();
+    sha3.update(&input);
+    sha3.finalize(&mut expected);
+    assert_eq!(sha3_256_short(&input), expected, "{} bytes", N);
+}
+
+#[test]
+fn sha3_256_short_matches_sha3_v256() {
+    check_sha3_256_short::<0>();
+    check_sha3_256_short::<1>();
+    check_sha3_256_short::<7>();
+    check_sha3_256_short::<8>();
+    check_sha3_256_short::<32>();
+    check_sha3_256_short::<64>();
+    check_sha3_256_short::<135>();
+}
+
+#[test]
+fn sha3_try_new() {
+    assert!(Sha3::<KeccakF>::try_new(384).is_ok());
+    assert_eq!(
+        Sha3::<KeccakF>::try_new(0).err(),
+        Some(Error::InvalidSecurityLevel(0))
+    );
+}
+
+#[test]
+#[should_panic(expected = "security level of 100 bits is not supported")]
+fn sha3_new_panics_on_invalid_security_level() {
+    Sha3::<KeccakF>::new(100);
+}
+
+#[test]
+fn sha3_save() {
+    let mut sha3 = Sha3::v256();
+    let mut out = [0u8; 32];
+    sha3.update(b"hello");
+    let mut buf = Vec::new();
+    sha3.serialize(&mut buf).unwrap();
+    sha3.update(b" world");
+    sha3.finalize(&mut out);
+
+    let buf = &mut buf.as_slice();
+    let mut sha3 = Sha3::deserialize(buf).unwrap();
+    sha3.update(b" world");
+    let mut out2 = [0u8; 32];
+    sha3.finalize(&mut out2);
+
+    assert_eq!(out, out2);
+}
+
+#[test]
+fn sha3_deserialize_rejects_other_hashers() {
+    let mut sha3 = Sha3::v256();
+    sha3.update(b"hello");
+    let buf = borsh::to_vec(&sha3).unwrap();
+    assert_eq!(0x06, buf[203]);
+
+    // a Keccak state has no suffix
+    assert!(Keccak::try_from_slice(&buf).is_err());
+    assert!(Sha3::try_from_slice(&buf[..203]).is_err());
+    let mut keccak = buf.clone();
+    keccak[203] = 0x01;
+    assert!(Sha3::try_from_slice(&keccak).is_err());
+    // a SHA3-256 state has the rate of SHAKE256, but not its suffix
+    assert!(Shake::try_from_slice(&buf).is_err());
+}
@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, Shake, Xof};
+use borsh::{BorshDeserialize, BorshSerialize};
+use tari_tiny_keccak::{Hasher, Shake, Xof};
 
 #[test]
 fn shake_xof_one() {
@@ -35,3 +36,56 @@ fn shake_xof_two() {
 
     assert_eq!(expected, &output);
 }
+
+#[test]
+fn shake_to_bytes_resumes_squeezing() {
+    let mut shake = Shake::v256();
+    shake.update(b"hello world");
+    let mut output = [0u8; 100];
+    shake.squeeze(&mut output);
+    let bytes = shake.to_bytes();
+    // squeezing, 100 bytes into the block
+    assert_eq!([136, 100, 0x1f, 2], bytes[200..]);
+
+    let mut restored: Shake = Shake::from_bytes(&bytes).unwrap();
+    let mut expected = [0u8; 300];
+    let mut restored_output = [0u8; 300];
+    shake.squeeze(&mut expected);
+    restored.squeeze(&mut restored_output);
+    assert_eq!(&expected[..], &restored_output[..]);
+}
+
+#[test]
+fn shake_save() {
+    let mut shake = Shake::v128();
+    let mut out = [0u8; 64];
+    shake.update(b"hello");
+    let mut buf = Vec::new();
+    shake.serialize(&mut buf).unwrap();
+    shake.update(b" world");
+    shake.finalize(&mut out);
+
+    let buf = &mut buf.as_slice();
+    let mut shake = Shake::deserialize(buf).unwrap();
+    shake.update(b" world");
+    let mut out2 = [0u8; 64];
+    shake.finalize(&mut out2);
+
+    assert_eq!(&out[..], &out2[..]);
+}
+
+#[test]
+fn shake_save_squeezing() {
+    let mut shake = Shake::v256();
+    let mut out = [0u8; 300];
+    shake.update(b"hello world");
+    shake.squeeze(&mut out[..100]);
+    let buf = borsh::to_vec(&shake).unwrap();
+    shake.squeeze(&mut out[100..]);
+
+    let mut restored = Shake::try_from_slice(&buf).unwrap();
+    let mut out2 = [0u8; 200];
+    restored.squeeze(&mut out2);
+
+    assert_eq!(&out[100..], &out2[..]);
+}
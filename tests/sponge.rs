@@ -0,0 +1,215 @@
+use borsh::BorshDeserialize;
+use tari_tiny_keccak::{CShake, Error, Hasher, Keccak, Mode, OutputMode, Sha3, Shake, Sponge, Xof};
+
+fn finalize<H: Hasher>(mut hasher: H, input: &[u8], output: &mut [u8]) {
+    hasher.update(input);
+    hasher.finalize(output);
+}
+
+#[test]
+fn test_sponge_defaults_to_keccak256() {
+    let mut expected = [0u8; 32];
+    let mut output = [0u8; 32];
+    finalize(Keccak::v256(), b"hello world", &mut expected);
+    finalize(Sponge::builder().build(), b"hello world", &mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn test_sponge_sha3() {
+    let mut expected = [0u8; 28];
+    let mut output = [0u8; 28];
+    finalize(Sha3::v224(), b"abc", &mut expected);
+    let sponge = Sponge::builder()
+        .capacity(448)
+        .suffix(0b10, 2)
+        .output(OutputMode::Fixed(28))
+        .build();
+    finalize(sponge, b"abc", &mut output);
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn test_sponge_shake() {
+    let mut expected = [0u8; 300];
+    let mut output = [0u8; 300];
+    let mut shake = Shake::v128();
+    shake.update(b"abc");
+    shake.squeeze(&mut expected);
+
+    let mut sponge = Sponge::builder().capacity(256).suffix(0b1111, 4).build();
+    sponge.update(b"abc");
+    sponge.squeeze(&mut output[..100]);
+    sponge.squeeze(&mut output[100..]);
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+fn test_sponge_cshake_without_name() {
+    // cSHAKE with an empty name and customization string is SHAKE
+    let mut expected = [0u8; 64];
+    let mut output = [0u8; 64];
+    finalize(CShake::v256(b"", b""), b"abc", &mut expected);
+    finalize(
+        Sponge::builder().capacity(512).suffix(0b1111, 4).build(),
+        b"abc",
+        &mut output,
+    );
+    assert_eq!(&expected[..], &output[..]);
+}
+
+#[test]
+fn test_sponge_keccak288() {
+    let expected = b"\
+        \x67\x53\xe3\x38\x0c\x09\xe3\x85\xd0\x33\x9e\xb6\xb0\x50\xa6\x8f\
+        \x66\xcf\xd6\x0a\x73\x47\x6e\x6f\xd6\xad\xeb\x72\xf5\xed\xd7\xc6\
+        \xf0\x4a\x5d\x01\
+    ";
+    let sponge = Sponge::builder()
+        .capacity(576)
+        .output(OutputMode::Fixed(36))
+        .build();
+    assert_eq!(128, sponge.rate());
+    assert_eq!(576, sponge.capacity());
+    let mut output = [0u8; 36];
+    finalize(sponge, b"", &mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_sponge_custom_suffix() {
+    let expected = b"\
+        \x5e\x50\xae\x6c\xed\x0a\x45\x4a\x86\x4f\xe9\xde\xd1\x5b\x2e\xb5\
+        \x33\xe2\x4b\x38\xd1\x30\xfe\x83\x58\x38\x78\x99\
+    ";
+    let sponge = Sponge::builder()
+        .capacity(448)
+        .suffix(0b011, 3)
+        .output(OutputMode::Fixed(28))
+        .build();
+    let mut output = [0u8; 28];
+    finalize(sponge, b"abc", &mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+#[should_panic(expected = "rate must be a whole number of bytes")]
+fn test_sponge_rejects_partial_byte_rate() {
+    Sponge::builder().capacity(500).build();
+}
+
+#[test]
+#[should_panic(expected = "rate must be at least 2 bytes and smaller than the width")]
+fn test_sponge_rejects_zero_capacity() {
+    Sponge::builder().capacity(0).build();
+}
+
+#[test]
+#[should_panic(expected = "rate must be at least 2 bytes and smaller than the width")]
+fn test_sponge_rejects_full_capacity() {
+    Sponge::builder().capacity(1600).build();
+}
+
+#[test]
+#[should_panic(expected = "suffix must fit in 2 bits")]
+fn test_sponge_rejects_oversized_suffix() {
+    Sponge::builder().suffix(0b100, 2).build();
+}
+
+#[test]
+#[should_panic(expected = "output must be 32 bytes long")]
+fn test_sponge_fixed_output_length() {
+    let sponge = Sponge::builder().output(OutputMode::Fixed(32)).build();
+    finalize(sponge, b"", &mut [0u8; 64]);
+}
+
+#[test]
+#[should_panic(expected = "only an extendable-output sponge can be squeezed")]
+fn test_sponge_fixed_output_cannot_be_squeezed() {
+    let mut sponge = Sponge::builder().output(OutputMode::Fixed(32)).build();
+    sponge.squeeze(&mut [0u8; 32]);
+}
+
+#[test]
+fn test_sponge_try_build() {
+    assert!(Sponge::builder().try_build().is_ok());
+    let errors = [
+        (Sponge::builder().capacity(500), Error::FractionalRate),
+        (Sponge::builder().capacity(1600), Error::RateOutOfRange),
+        (Sponge::builder().suffix(0, 7), Error::SuffixTooLong),
+        (Sponge::builder().suffix(0b100, 2), Error::SuffixOverflow(2)),
+    ];
+    for (builder, error) in errors.iter() {
+        assert_eq!(builder.try_build().err().unwrap(), *error);
+    }
+}
+
+#[cfg(feature = "reduced_width")]
+#[test]
+fn test_sponge_rate_smaller_than_permutation_width() {
+    use tari_tiny_keccak::KeccakF200;
+
+    // the default capacity of 512 bits doesn't fit in the 200 bits of keccak-f[200]
+    let builder = Sponge::builder().permutation::<KeccakF200>();
+    assert_eq!(builder.try_build().err().unwrap(), Error::RateOutOfRange);
+    assert_eq!(
+        builder.capacity(0).try_build().err().unwrap(),
+        Error::RateOutOfRange
+    );
+
+    let mut sponge = builder.capacity(56).build();
+    assert_eq!(18, sponge.rate());
+    assert_eq!(56, sponge.capacity());
+    for _ in 0..30 {
+        sponge.update(b"abc");
+    }
+    let expected = b"\
+        \xfe\x1d\x76\xb5\x6b\x6c\xe8\x61\xc4\xde\xd9\x1f\x36\x11\x90\x46\
+        \x7c\x77\x1e\x02\xd5\x6a\xdf\x68\x76\x43\xd5\x85\x8f\xcf\x98\x82\
+        \xb2\xb0\xa9\xa4\x8a\xb6\x3e\xaf\
+    ";
+    let mut output = [0u8; 40];
+    sponge.squeeze(&mut output);
+    assert_eq!(expected, &output);
+}
+
+#[test]
+fn test_sponge_checkpoint() {
+    let mut sponge = Sponge::builder().capacity(576).suffix(0b10, 2).build();
+    sponge.update(b"hello");
+    let buf = borsh::to_vec(&sponge).unwrap();
+    let mut restored = Sponge::try_from_slice(&buf).unwrap();
+    assert_eq!(restored.rate(), sponge.rate());
+    assert_eq!(restored.output_mode(), sponge.output_mode());
+
+    let mut output = [0u8; 64];
+    let mut output2 = [0u8; 64];
+    sponge.update(b" world");
+    sponge.squeeze(&mut output[..20]);
+    restored.update(b" world");
+    restored.squeeze(&mut output2[..20]);
+    let mut restored = Sponge::try_from_slice(&borsh::to_vec(&restored).unwrap()).unwrap();
+    sponge.squeeze(&mut output[20..]);
+    restored.squeeze(&mut output2[20..]);
+    assert_eq!(&output[..], &output2[..]);
+}
+
+#[test]
+fn test_sponge_checkpoint_rejected() {
+    let mut sponge = Sponge::builder().output(OutputMode::Fixed(32)).build();
+    sponge.update(b"hello");
+    let buf = borsh::to_vec(&sponge).unwrap();
+    let err = |buf: &[u8]| Sponge::try_from_slice(buf).err().unwrap().to_string();
+
+    // the lanes are followed by the offset, the rate, the mode, the suffix and the output mode
+    let mut squeezing = buf.clone();
+    squeezing[202] = Mode::Squeezing as u8;
+    assert_eq!(err(&squeezing), Error::InvalidState.to_string());
+    let mut suffix = buf.clone();
+    suffix[203] = 0x80;
+    assert_eq!(err(&suffix), Error::InvalidState.to_string());
+    let mut rate = buf.clone();
+    rate[201] = 200;
+    assert_eq!(err(&rate), Error::InvalidState.to_string());
+    assert!(Sponge::try_from_slice(&buf[..buf.len() - 1]).is_err());
+}
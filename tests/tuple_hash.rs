@@ -1,4 +1,5 @@
-use tiny_keccak::{Hasher, TupleHash};
+use borsh::{io, BorshDeserialize};
+use tari_tiny_keccak::{Error, Hasher, IntoXof, Mode, TupleHash, TupleHashXof, Xof};
 
 #[test]
 fn test_tuple_hash128_one() {
@@ -111,3 +112,45 @@ fn test_tuple_hash256_three() {
     hasher.finalize(&mut output);
     assert_eq!(expected as &[u8], &output as &[u8]);
 }
+
+#[test]
+fn test_tuple_hash_checkpoint() {
+    let mut tuple_hash = TupleHash::v128(b"custom");
+    tuple_hash.update(b"abc");
+    let buf = borsh::to_vec(&tuple_hash).unwrap();
+    tuple_hash.update(b"d");
+    let mut xof = tuple_hash.into_xof();
+    let mut output = [0u8; 64];
+    xof.squeeze(&mut output[..32]);
+    let xof_buf = borsh::to_vec(&xof).unwrap();
+    xof.squeeze(&mut output[32..]);
+
+    let mut restored = TupleHash::try_from_slice(&buf).unwrap();
+    restored.update(b"d");
+    let mut output2 = [0u8; 64];
+    restored.into_xof().squeeze(&mut output2);
+    assert_eq!(&output[..], &output2[..]);
+
+    let mut restored = TupleHashXof::try_from_slice(&xof_buf).unwrap();
+    restored.squeeze(&mut output2[..32]);
+    assert_eq!(&output[32..], &output2[..32]);
+
+    assert_eq!(
+        TupleHashXof::try_from_slice(&buf)
+            .err()
+            .unwrap()
+            .to_string(),
+        Error::AlgorithmMismatch.to_string()
+    );
+    // the header and the lanes are followed by the offset, the rate and the mode
+    let mut squeezing = buf.clone();
+    squeezing[206] = Mode::Squeezing as u8;
+    let err = TupleHash::try_from_slice(&squeezing).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(err.to_string(), Error::InvalidState.to_string());
+    #[cfg(feature = "std")]
+    assert_eq!(
+        err.get_ref().unwrap().downcast_ref::<Error>(),
+        Some(&Error::InvalidState)
+    );
+}
@@ -0,0 +1,362 @@
+use tari_tiny_keccak::{Error, Hasher, TurboShake, Xof};
+
+fn pattern(len: usize) -> Vec<u8> {
+    (0..len).map(|j| (j % 251) as u8).collect()
+}
+
+fn test_turbo_shake<A: AsRef<[u8]>>(
+    mut hasher: TurboShake,
+    message: A,
+    output_len: usize,
+    expected: &[u8],
+) {
+    hasher.update(message.as_ref());
+    let mut res = vec![0; output_len];
+    hasher.finalize(&mut res);
+    assert_eq!(&res[output_len - expected.len()..], expected);
+}
+
+#[test]
+fn turbo_shake128_empty() {
+    let expected = b"\
+        \x1e\x41\x5f\x1c\x59\x83\xaf\xf2\x16\x92\x17\x27\x7d\x17\xbb\x53\
+        \x8c\xd9\x45\xa3\x97\xdd\xec\x54\x1f\x1c\xe4\x1a\xf2\xc1\xb7\x4c\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), "", 32, expected);
+}
+
+#[test]
+fn turbo_shake128_long() {
+    let expected = b"\
+        \xa3\xb9\xb0\x38\x59\x00\xce\x76\x1f\x22\xae\xd5\x48\xe7\x54\xda\
+        \x10\xa5\x24\x2d\x62\xe8\xc6\x58\xe3\xf3\xa9\x23\xa7\x55\x56\x07\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), "", 10032, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message() {
+    let expected = b"\
+        \x55\xce\xdd\x6f\x60\xaf\x7b\xb2\x9a\x40\x42\xae\x83\x2e\xf3\xf5\
+        \x8d\xb7\x29\x9f\x89\x3e\xbb\x92\x47\x24\x7d\x85\x69\x58\xda\xa9\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(1), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message_17() {
+    let expected = b"\
+        \x9c\x97\xd0\x36\xa3\xba\xc8\x19\xdb\x70\xed\xe0\xca\x55\x4e\xc6\
+        \xe4\xc2\xa1\xa4\xff\xbf\xd9\xec\x26\x9c\xa6\xa1\x11\x16\x12\x33\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(17), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message_289() {
+    let expected = b"\
+        \x96\xc7\x7c\x27\x9e\x01\x26\xf7\xfc\x07\xc9\xb0\x7f\x5c\xda\xe1\
+        \xe0\xbe\x60\xbd\xbe\x10\x62\x00\x40\xe7\x5d\x72\x23\xa6\x24\xd2\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(289), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message_4913() {
+    let expected = b"\
+        \xd4\x97\x6e\xb5\x6b\xcf\x11\x85\x20\x58\x2b\x70\x9f\x73\xe1\xd6\
+        \x85\x3e\x00\x1f\xda\xf8\x0e\x1b\x13\xe0\xd0\x59\x9d\x5f\xb3\x72\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(4913), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message_83521() {
+    let expected = b"\
+        \xda\x67\xc7\x03\x9e\x98\xbf\x53\x0c\xf7\xa3\x78\x30\xc6\x66\x4e\
+        \x14\xcb\xab\x7f\x54\x0f\x58\x40\x3b\x1b\x82\x95\x13\x18\xee\x5c\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(83521), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_with_message_1419857() {
+    let expected = b"\
+        \xb9\x7a\x90\x6f\xbf\x83\xef\x7c\x81\x25\x17\xab\xf3\xb2\xd0\xae\
+        \xa0\xc4\xf6\x03\x18\xce\x11\xcf\x10\x39\x25\x12\x7f\x59\xee\xcd\
+    ";
+    test_turbo_shake(TurboShake::v128(0x1f), pattern(1419857), 32, expected);
+}
+
+#[test]
+fn turbo_shake128_domain_01() {
+    let expected = b"\
+        \xbf\x32\x3f\x94\x04\x94\xe8\x8e\xe1\xc5\x40\xfe\x66\x0b\xe8\xa0\
+        \xc9\x3f\x43\xd1\x5e\xc0\x06\x99\x84\x62\xfa\x99\x4e\xed\x5d\xab\
+    ";
+    test_turbo_shake(TurboShake::v128(0x01), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turbo_shake128_domain_06() {
+    let expected = b"\
+        \x8e\xc9\xc6\x64\x65\xed\x0d\x4a\x6c\x35\xd1\x35\x06\x71\x8d\x68\
+        \x7a\x25\xcb\x05\xc7\x4c\xca\x1e\x42\x50\x1a\xbd\x83\x87\x4a\x67\
+    ";
+    test_turbo_shake(TurboShake::v128(0x06), [0xff], 32, expected);
+}
+
+#[test]
+fn turbo_shake128_domain_07() {
+    let expected = b"\
+        \xb6\x58\x57\x60\x01\xca\xd9\xb1\xe5\xf3\x99\xa9\xf7\x77\x23\xbb\
+        \xa0\x54\x58\x04\x2d\x68\x20\x6f\x72\x52\x68\x2d\xba\x36\x63\xed\
+    ";
+    test_turbo_shake(TurboShake::v128(0x07), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turbo_shake128_domain_0b() {
+    let expected = b"\
+        \x8d\xee\xaa\x1a\xec\x47\xcc\xee\x56\x9f\x65\x9c\x21\xdf\xa8\xe1\
+        \x12\xdb\x3c\xee\x37\xb1\x81\x78\xb2\xac\xd8\x05\xb7\x99\xcc\x37\
+    ";
+    test_turbo_shake(
+        TurboShake::v128(0x0b),
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        32,
+        expected,
+    );
+}
+
+#[test]
+fn turbo_shake128_domain_30() {
+    let expected = b"\
+        \x55\x31\x22\xe2\x13\x5e\x36\x3c\x32\x92\xbe\xd2\xc6\x42\x1f\xa2\
+        \x32\xba\xb0\x3d\xaa\x07\xc7\xd6\x63\x66\x03\x28\x65\x06\x32\x5b\
+    ";
+    test_turbo_shake(TurboShake::v128(0x30), [0xff], 32, expected);
+}
+
+#[test]
+fn turbo_shake128_domain_7f() {
+    let expected = b"\
+        \x16\x27\x4c\xc6\x56\xd4\x4c\xef\xd4\x22\x39\x5d\x0f\x90\x53\xbd\
+        \xa6\xd2\x8e\x12\x2a\xba\x15\xc7\x65\xe5\xad\x0e\x6e\xaf\x26\xf9\
+    ";
+    test_turbo_shake(TurboShake::v128(0x7f), [0xff, 0xff, 0xff], 32, expected);
+}
+
+#[test]
+fn turbo_shake256_empty() {
+    let expected = b"\
+        \x36\x7a\x32\x9d\xaf\xea\x87\x1c\x78\x02\xec\x67\xf9\x05\xae\x13\
+        \xc5\x76\x95\xdc\x2c\x66\x63\xc6\x10\x35\xf5\x9a\x18\xf8\xe7\xdb\
+        \x11\xed\xc0\xe1\x2e\x91\xea\x60\xeb\x6b\x32\xdf\x06\xdd\x7f\x00\
+        \x2f\xba\xfa\xbb\x6e\x13\xec\x1c\xc2\x0d\x99\x55\x47\x60\x0d\xb0\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), "", 64, expected);
+}
+
+#[test]
+fn turbo_shake256_long() {
+    let expected = b"\
+        \xab\xef\xa1\x16\x30\xc6\x61\x26\x92\x49\x74\x26\x85\xec\x08\x2f\
+        \x20\x72\x65\xdc\xcf\x2f\x43\x53\x4e\x9c\x61\xba\x0c\x9d\x1d\x75\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), "", 10032, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message() {
+    let expected = b"\
+        \x3e\x17\x12\xf9\x28\xf8\xea\xf1\x05\x46\x32\xb2\xaa\x0a\x24\x6e\
+        \xd8\xb0\xc3\x78\x72\x8f\x60\xbc\x97\x04\x10\x15\x5c\x28\x82\x0e\
+        \x90\xcc\x90\xd8\xa3\x00\x6a\xa2\x37\x2c\x5c\x5e\xa1\x76\xb0\x68\
+        \x2b\xf2\x2b\xae\x74\x67\xac\x94\xf7\x4d\x43\xd3\x9b\x04\x82\xe2\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(1), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message_17() {
+    let expected = b"\
+        \xb3\xba\xb0\x30\x0e\x6a\x19\x1f\xbe\x61\x37\x93\x98\x35\x92\x35\
+        \x78\x79\x4e\xa5\x48\x43\xf5\x01\x10\x90\xfa\x2f\x37\x80\xa9\xe5\
+        \xcb\x22\xc5\x9d\x78\xb4\x0a\x0f\xbf\xf9\xe6\x72\xc0\xfb\xe0\x97\
+        \x0b\xd2\xc8\x45\x09\x1c\x60\x44\xd6\x87\x05\x4d\xa5\xd8\xe9\xc7\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(17), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message_289() {
+    let expected = b"\
+        \x66\xb8\x10\xdb\x8e\x90\x78\x04\x24\xc0\x84\x73\x72\xfd\xc9\x57\
+        \x10\x88\x2f\xde\x31\xc6\xdf\x75\xbe\xb9\xd4\xcd\x93\x05\xcf\xca\
+        \xe3\x5e\x7b\x83\xe8\xb7\xe6\xeb\x4b\x78\x60\x58\x80\x11\x63\x16\
+        \xfe\x2c\x07\x8a\x09\xb9\x4a\xd7\xb8\x21\x3c\x0a\x73\x8b\x65\xc0\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(289), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message_4913() {
+    let expected = b"\
+        \xc7\x4e\xbc\x91\x9a\x5b\x3b\x0d\xd1\x22\x81\x85\xba\x02\xd2\x9e\
+        \xf4\x42\xd6\x9d\x3d\x42\x76\xa9\x3e\xfe\x0b\xf9\xa1\x6a\x7d\xc0\
+        \xcd\x4e\xab\xad\xab\x8c\xd7\xa5\xed\xd9\x66\x95\xf5\xd3\x60\xab\
+        \xe0\x9e\x2c\x65\x11\xa3\xec\x39\x7d\xa3\xb7\x6b\x9e\x16\x74\xfb\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(4913), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message_83521() {
+    let expected = b"\
+        \x02\xcc\x3a\x88\x97\xe6\xf4\xf6\xcc\xb6\xfd\x46\x63\x1b\x1f\x52\
+        \x07\xb6\x6c\x6d\xe9\xc7\xb5\x5b\x2d\x1a\x23\x13\x4a\x17\x0a\xfd\
+        \xac\x23\x4e\xab\xa9\xa7\x7c\xff\x88\xc1\xf0\x20\xb7\x37\x24\x61\
+        \x8c\x56\x87\xb3\x62\xc4\x30\xb2\x48\xcd\x38\x64\x7f\x84\x8a\x1d\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(83521), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_with_message_1419857() {
+    let expected = b"\
+        \xad\xd5\x3b\x06\x54\x3e\x58\x4b\x58\x23\xf6\x26\x99\x6a\xee\x50\
+        \xfe\x45\xed\x15\xf2\x02\x43\xa7\x16\x54\x85\xac\xb4\xaa\x76\xb4\
+        \xff\xda\x75\xce\xdf\x6d\x8c\xdc\x95\xc3\x32\xbd\x56\xf4\xb9\x86\
+        \xb5\x8b\xb1\x7d\x17\x78\xbf\xc1\xb1\xa9\x75\x45\xcd\xf4\xec\x9f\
+    ";
+    test_turbo_shake(TurboShake::v256(0x1f), pattern(1419857), 64, expected);
+}
+
+#[test]
+fn turbo_shake256_domain_01() {
+    let expected = b"\
+        \xd2\x1c\x6f\xbb\xf5\x87\xfa\x22\x82\xf2\x9a\xea\x62\x01\x75\xfb\
+        \x02\x57\x41\x3a\xf7\x8a\x0b\x1b\x2a\x87\x41\x9c\xe0\x31\xd9\x33\
+        \xae\x7a\x4d\x38\x33\x27\xa8\xa1\x76\x41\xa3\x4f\x8a\x1d\x10\x03\
+        \xad\x7d\xa6\xb7\x2d\xba\x84\xbb\x62\xfe\xf2\x8f\x62\xf1\x24\x24\
+    ";
+    test_turbo_shake(TurboShake::v256(0x01), [0xff, 0xff, 0xff], 64, expected);
+}
+
+#[test]
+fn turbo_shake256_domain_06() {
+    let expected = b"\
+        \x73\x8d\x7b\x4e\x37\xd1\x8b\x7f\x22\xad\x1b\x53\x13\xe3\x57\xe3\
+        \xdd\x7d\x07\x05\x6a\x26\xa3\x03\xc4\x33\xfa\x35\x33\x45\x52\x80\
+        \xf4\xf5\xa7\xd4\xf7\x00\xef\xb4\x37\xfe\x6d\x28\x14\x05\xe0\x7b\
+        \xe3\x2a\x0a\x97\x2e\x22\xe6\x3a\xdc\x1b\x09\x0d\xae\xfe\x00\x4b\
+    ";
+    test_turbo_shake(TurboShake::v256(0x06), [0xff], 64, expected);
+}
+
+#[test]
+fn turbo_shake256_domain_07() {
+    let expected = b"\
+        \x18\xb3\xb5\xb7\x06\x1c\x2e\x67\xc1\x75\x3a\x00\xe6\xad\x7e\xd7\
+        \xba\x1c\x90\x6c\xf9\x3e\xfb\x70\x92\xea\xf2\x7f\xbe\xeb\xb7\x55\
+        \xae\x6e\x29\x24\x93\xc1\x10\xe4\x8d\x26\x00\x28\x49\x2b\x8e\x09\
+        \xb5\x50\x06\x12\xb8\xf2\x57\x89\x85\xde\xd5\x35\x7d\x00\xec\x67\
+    ";
+    test_turbo_shake(TurboShake::v256(0x07), [0xff, 0xff, 0xff], 64, expected);
+}
+
+#[test]
+fn turbo_shake256_domain_0b() {
+    let expected = b"\
+        \xbb\x36\x76\x49\x51\xec\x97\xe9\xd8\x5f\x7e\xe9\xa6\x7a\x77\x18\
+        \xfc\x00\x5c\xf4\x25\x56\xbe\x79\xce\x12\xc0\xbd\xe5\x0e\x57\x36\
+        \xd6\x63\x2b\x0d\x0d\xfb\x20\x2d\x1b\xbb\x8f\xfe\x3d\xd7\x4c\xb0\
+        \x08\x34\xfa\x75\x6c\xb0\x34\x71\xba\xb1\x3a\x1e\x2c\x16\xb3\xc0\
+    ";
+    test_turbo_shake(
+        TurboShake::v256(0x0b),
+        [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff],
+        64,
+        expected,
+    );
+}
+
+#[test]
+fn turbo_shake256_domain_30() {
+    let expected = b"\
+        \xf3\xfe\x12\x87\x3d\x34\xbc\xbb\x2e\x60\x87\x79\xd6\xb7\x0e\x7f\
+        \x86\xbe\xc7\xe9\x0b\xf1\x13\xcb\xd4\xfd\xd0\xc4\xe2\xf4\x62\x5e\
+        \x14\x8d\xd7\xee\x1a\x52\x77\x6c\xf7\x7f\x24\x05\x14\xd9\xcc\xfc\
+        \x3b\x5d\xda\xb8\xee\x25\x5e\x39\xee\x38\x90\x72\x96\x2c\x11\x1a\
+    ";
+    test_turbo_shake(TurboShake::v256(0x30), [0xff], 64, expected);
+}
+
+#[test]
+fn turbo_shake256_domain_7f() {
+    let expected = b"\
+        \xab\xe5\x69\xc1\xf7\x7e\xc3\x40\xf0\x27\x05\xe7\xd3\x7c\x9a\xb7\
+        \xe1\x55\x51\x6e\x4a\x6a\x15\x00\x21\xd7\x0b\x6f\xac\x0b\xb4\x0c\
+        \x06\x9f\x9a\x98\x28\xa0\xd5\x75\xcd\x99\xf9\xba\xe4\x35\xab\x1a\
+        \xcf\x7e\xd9\x11\x0b\xa9\x7c\xe0\x38\x8d\x07\x4b\xac\x76\x87\x76\
+    ";
+    test_turbo_shake(TurboShake::v256(0x7f), [0xff, 0xff, 0xff], 64, expected);
+}
+
+#[test]
+fn turbo_shake_squeeze_in_parts() {
+    let mut expected = [0u8; 64];
+    let mut hasher = TurboShake::v128(0x1f);
+    hasher.update(&pattern(17));
+    hasher.clone().finalize(&mut expected);
+
+    let mut output = [0u8; 64];
+    hasher.squeeze(&mut output[..17]);
+    hasher.squeeze(&mut output[17..]);
+    assert_eq!(expected, output);
+}
+
+#[test]
+#[should_panic]
+fn turbo_shake_zero_domain_byte() {
+    TurboShake::v128(0x00);
+}
+
+#[test]
+#[should_panic]
+fn turbo_shake_domain_byte_too_large() {
+    TurboShake::v256(0x80);
+}
+
+#[test]
+fn turbo_shake_to_bytes_resumes() {
+    let mut hasher = TurboShake::v128(0x0b);
+    hasher.update(&pattern(1000));
+    let bytes = hasher.to_bytes();
+    assert_eq!(0x0b, bytes[202]);
+
+    let restored = TurboShake::from_bytes(&bytes).unwrap();
+    let mut output = [0u8; 64];
+    let mut restored_output = [0u8; 64];
+    hasher.finalize(&mut output);
+    restored.finalize(&mut restored_output);
+    assert_eq!(&output[..], &restored_output[..]);
+}
+
+#[test]
+fn turbo_shake_try_new() {
+    assert!(TurboShake::try_v128(0x1f).is_ok());
+    assert_eq!(
+        TurboShake::try_v128(0x00).err().unwrap(),
+        Error::InvalidDomainByte(0x00)
+    );
+    assert_eq!(
+        TurboShake::try_v256(0x80).err().unwrap(),
+        Error::InvalidDomainByte(0x80)
+    );
+    assert_eq!(
+        Error::InvalidDomainByte(0x80).to_string(),
+        "domain separation byte 0x80 is not in range 0x01..=0x7F"
+    );
+}